@@ -1,8 +1,18 @@
 //! Datatypes that are present inside our generated ASTs.
+use std::cell::RefCell;
 use std::fmt::{self, Display};
+use std::rc::Rc;
 
+use fxhash::FxHashMap;
+
+use crate::bigint::BigInt;
+use crate::callable::Callable;
+use crate::class::{Class, Instance};
 use crate::literals::Literal;
+use crate::mapkey::MapKey;
+use crate::module::Module;
 use crate::nativefn::NativeFn;
+use crate::rational::Rational;
 use crate::token::Token;
 use crate::userfunction::UserFunction;
 
@@ -19,12 +29,25 @@ pub enum ASTNode {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Stmt {
     Block(Vec<Stmt>),
+    Break(Token),
+    Class(Token, Option<Expr>, Vec<Stmt>),
+    Continue(Token),
     Expression(Expr),
-    Function(Token, Vec<Token>, Vec<Stmt>),
+    Function(Token, Vec<Param>, Vec<Stmt>),
     If(Expr, Box<Stmt>, Box<Option<Stmt>>),
     Return(Token, Option<Expr>),
+    /// The invocabo keyword, and the path of the ritual being loaded.
+    Import(Token, String),
+    /// The iacere keyword, and the error being raised.
+    Throw(Token, Expr),
+    /// The body of a conare, the name the error is bound to, and the capere
+    /// block that handles it.
+    Try(Vec<Stmt>, Token, Vec<Stmt>),
     Var(Token, Option<Expr>),
-    While(Expr, Box<Stmt>),
+    /// A constans, which always has an initializer.
+    Const(Token, Expr),
+    /// Condition, body and (for desugared enim loops) the increment.
+    While(Expr, Box<Stmt>, Option<Expr>),
     Print(Expr),
 }
 
@@ -34,14 +57,72 @@ pub enum Stmt {
 pub enum Expr {
     Assign(Token, Box<Expr>),
     Binary(Box<Expr>, Token, Box<Expr>),
-    Call(Box<Expr>, Token, Vec<Expr>),
+    /// The callee, the closing paren, the positional arguments and the
+    /// arguments that were passed by name.
+    Call(Box<Expr>, Token, Vec<Expr>, Vec<(Token, Expr)>),
+    /// cond ? a : b. The condition, and the two branches.
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Get(Box<Expr>, Token),
     Grouping(Box<Expr>),
+    /// An interpolated string. The token of it's first piece, then the pieces
+    /// of literal text and the interpolated expressions, in order.
+    Interpolate(Token, Vec<Expr>),
+    /// The indexed expression, the opening bracket, and the index.
+    Index(Box<Expr>, Token, Box<Expr>),
+    List(Vec<Expr>),
     Literal(Literal),
+    /// An anonymous incantatio. The keyword, the parameters and the body.
+    Lambda(Token, Vec<Param>, Vec<Stmt>),
     Logic(Box<Expr>, Token, Box<Expr>),
+    /// The opening brace, and each of the key: value pairs.
+    Map(Token, Vec<(Expr, Expr)>),
+    Set(Box<Expr>, Token, Box<Expr>),
+    /// Same as an Index, but with the value being assigned to it.
+    SetIndex(Box<Expr>, Token, Box<Expr>, Box<Expr>),
+    Self_(Token),
+    Super(Token, Token),
     Unary(Token, Box<Expr>),
+    /// A compound assignment to a field or an element, eg. a.b += 1. The
+    /// target (a Get or an Index), the binary operator to apply, and the
+    /// value. Variables are just desugared into an Assign instead.
+    Update(Box<Expr>, Token, Box<Expr>),
     Variable(Token),
 }
 
+/// A parameter of an incantatio. Parameters may have a default value, which is
+/// evaluated each time the function is called without that argument. The last
+/// parameter may instead be a rest parameter (`...name`), which collects any
+/// extra positional arguments into a list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: Token,
+    pub default: Option<Expr>,
+    pub rest: bool,
+}
+
+impl Param {
+    /// A plain parameter, with no default.
+    pub fn new(name: Token) -> Param {
+        Param {
+            name,
+            default: None,
+            rest: false,
+        }
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.rest {
+            return write!(f, "...{}", self.name.lexeme);
+        }
+        match &self.default {
+            Some(default) => write!(f, "{} = {}", self.name.lexeme, default),
+            None => write!(f, "{}", self.name.lexeme),
+        }
+    }
+}
+
 /// We need some way to store state inside the interpreter. This is how.
 ///
 /// Once again, we use ADTs in order to define the possible variants of a Value.
@@ -52,18 +133,47 @@ pub enum Expr {
 /// If it's data, and it's (nominally) mutable at runtime, it's probably a Value.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
+    Integer(i64),
+    /// Only for integers too big to be an Integer.
+    BigInt(Rc<BigInt>),
+    /// Only for fractions that aren't whole numbers.
+    Rational(Rc<Rational>),
     Number(f64),
     Bool(bool),
     String(String),
     NativeFn(NativeFn),
     UserFn(UserFunction),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<FxHashMap<MapKey, Value>>>),
+    Module(Rc<Module>),
     Empty,
 }
 
+impl Value {
+    /// Values that can be called. This is the only place that needs to know
+    /// which kinds of value those are.
+    pub fn as_callable(&self) -> Option<&dyn Callable> {
+        match self {
+            Value::NativeFn(f) => Some(f),
+            Value::UserFn(f) => Some(f),
+            Value::Class(c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
 /// This allows us to convert from Literals to values.
 impl From<Literal> for Value {
     fn from(lit: Literal) -> Self {
         match lit {
+            Literal::Integer(v) => {
+                return Value::Integer(v);
+            }
+            Literal::BigInt(v) => {
+                return Value::BigInt(v);
+            }
             Literal::Number(v) => {
                 return Value::Number(v);
             }
@@ -83,6 +193,15 @@ impl From<Literal> for Value {
 impl Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Value::Integer(x) => {
+                write!(f, "{}", x)
+            }
+            Value::BigInt(x) => {
+                write!(f, "{}", x)
+            }
+            Value::Rational(x) => {
+                write!(f, "{}", x)
+            }
             Value::Number(x) => {
                 write!(f, "{}", x)
             }
@@ -101,6 +220,27 @@ impl Display for Value {
             Value::UserFn(x) => {
                 write!(f, "{}", x)
             }
+            Value::Class(x) => {
+                write!(f, "{}", x)
+            }
+            Value::Instance(x) => {
+                write!(f, "{}", x.borrow())
+            }
+            Value::Module(x) => {
+                write!(f, "{}", x)
+            }
+            Value::List(x) => {
+                let items: Vec<String> = x.borrow().iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Map(x) => {
+                let map = x.borrow();
+                let mut keys: Vec<&MapKey> = map.keys().collect();
+                keys.sort();
+                let items: Vec<String> =
+                    keys.iter().map(|k| format!("{}: {}", k, map[*k])).collect();
+                write!(f, "{{{}}}", items.join(", "))
+            }
         }
     }
 }
@@ -115,6 +255,22 @@ impl Display for Stmt {
                 }
                 write!(f, "{} )", rv)
             }
+            Stmt::Break(_) => {
+                write!(f, "frange")
+            }
+            Stmt::Continue(_) => {
+                write!(f, "perge")
+            }
+            Stmt::Class(name, superclass, methods) => {
+                let mut rv = match superclass {
+                    Some(parent) => format!("Daemonium {} < {}: {{", name, parent),
+                    None => format!("Daemonium {}: {{", name),
+                };
+                for method in methods {
+                    rv = format!("{} {}", rv, method);
+                }
+                write!(f, "{} }}", rv)
+            }
             Stmt::Expression(expr) => {
                 write!(f, "EXPR: ( {} )", expr)
             }
@@ -128,8 +284,20 @@ impl Display for Stmt {
             }
             Stmt::If(_, _, _) => todo!(),
             Stmt::Return(_, _) => todo!(),
+            Stmt::Import(_, path) => {
+                write!(f, "invocabo \"{}\"", path)
+            }
+            Stmt::Throw(_, val) => {
+                write!(f, "iacere {}", val)
+            }
+            Stmt::Try(_, name, _) => {
+                write!(f, "conare {{ ... }} capere ({}) {{ ... }}", name.lexeme)
+            }
             Stmt::Var(_, _) => todo!(),
-            Stmt::While(_, _) => todo!(),
+            Stmt::Const(name, val) => {
+                write!(f, "constans {} = {}", name.lexeme, val)
+            }
+            Stmt::While(_, _, _) => todo!(),
             Stmt::Print(_) => todo!(),
         }
     }
@@ -138,16 +306,76 @@ impl Display for Stmt {
 impl Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Expr::Assign(_, _) => todo!(),
-            Expr::Binary(_, _, _) => todo!(),
-            Expr::Call(_, _, _) => todo!(),
-            Expr::Grouping(_) => todo!(),
+            Expr::Assign(name, value) => {
+                write!(f, "{} = {}", name.lexeme, value)
+            }
+            Expr::Binary(left, oper, right) => {
+                write!(f, "{} {} {}", left, oper.lexeme, right)
+            }
+            Expr::Call(callee, _, args, named) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|a| a.to_string())
+                    .chain(named.iter().map(|(n, a)| format!("{}: {}", n.lexeme, a)))
+                    .collect();
+                write!(f, "{}({})", callee, args.join(", "))
+            }
+            Expr::Conditional(cond, then, otherwise) => {
+                write!(f, "{} ? {} : {}", cond, then, otherwise)
+            }
+            Expr::Get(object, name) => {
+                write!(f, "{}.{}", object, name.lexeme)
+            }
+            Expr::Grouping(expr) => {
+                write!(f, "({})", expr)
+            }
+            Expr::Index(object, _, index) => {
+                write!(f, "{}[{}]", object, index)
+            }
+            Expr::List(items) => {
+                let items: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
             Expr::Literal(x) => {
                 write!(f, "\"{}\"", x)
             }
-            Expr::Logic(_, _, _) => todo!(),
-            Expr::Unary(_, _) => todo!(),
-            Expr::Variable(_) => todo!(),
+            Expr::Interpolate(_, parts) => {
+                let parts: Vec<String> = parts.iter().map(|x| x.to_string()).collect();
+                write!(f, "interpolate({})", parts.join(", "))
+            }
+            Expr::Lambda(_, params, _) => {
+                let params: Vec<String> = params.iter().map(|x| x.to_string()).collect();
+                write!(f, "incantatio ({})", params.join(", "))
+            }
+            Expr::Logic(left, oper, right) => {
+                write!(f, "{} {} {}", left, oper.lexeme, right)
+            }
+            Expr::Map(_, pairs) => {
+                let items: Vec<String> =
+                    pairs.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                write!(f, "{{{}}}", items.join(", "))
+            }
+            Expr::Set(object, name, value) => {
+                write!(f, "{}.{} = {}", object, name.lexeme, value)
+            }
+            Expr::SetIndex(object, _, index, value) => {
+                write!(f, "{}[{}] = {}", object, index, value)
+            }
+            Expr::Self_(_) => {
+                write!(f, "anima")
+            }
+            Expr::Super(_, method) => {
+                write!(f, "cognatio.{}", method.lexeme)
+            }
+            Expr::Unary(oper, right) => {
+                write!(f, "{}{}", oper.lexeme, right)
+            }
+            Expr::Update(target, oper, value) => {
+                write!(f, "{} {}= {}", target, oper.lexeme, value)
+            }
+            Expr::Variable(name) => {
+                write!(f, "{}", name.lexeme)
+            }
         }
     }
 }
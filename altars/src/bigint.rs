@@ -0,0 +1,371 @@
+//! Arbitrary precision integers.
+//!
+//! These only show up once an integer gets too big to fit in an i64, so they
+//! are built for being correct rather than fast. Numbers are stored as a sign
+//! and a magnitude of base 2^32 digits, least significant first.
+use core::fmt;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The magnitude never has leading (most significant) zero digits, so zero is
+/// an empty magnitude, and zero is never negative. This keeps every number to
+/// one representation, so that the derived Eq and Hash are correct.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    mag: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt {
+            negative: false,
+            mag: Vec::new(),
+        }
+    }
+
+    pub fn from_i64(x: i64) -> BigInt {
+        let abs = x.unsigned_abs();
+        BigInt::from_parts(x < 0, vec![abs as u32, (abs >> 32) as u32])
+    }
+
+    fn from_parts(negative: bool, mut mag: Vec<u32>) -> BigInt {
+        while mag.last() == Some(&0) {
+            mag.pop();
+        }
+        BigInt {
+            negative: negative && !mag.is_empty(),
+            mag,
+        }
+    }
+
+    /// Parse the digits of an integer in the given radix, with an optional
+    /// leading minus sign.
+    pub fn parse(text: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let mut mag: Vec<u32> = Vec::new();
+        for c in digits.chars() {
+            let d = c.to_digit(radix)?;
+            mag = mul_small(&mag, radix, d);
+        }
+        Some(BigInt::from_parts(negative, mag))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.mag.len() > 2 {
+            return None;
+        }
+        let mut abs: u64 = 0;
+        for digit in self.mag.iter().rev() {
+            abs = (abs << 32) | *digit as u64;
+        }
+        if self.negative {
+            0i64.checked_sub_unsigned(abs)
+        } else {
+            i64::try_from(abs).ok()
+        }
+    }
+
+    pub fn to_u32(&self) -> Option<u32> {
+        match self.mag.len() {
+            0 => Some(0),
+            1 if !self.negative => Some(self.mag[0]),
+            _ => None,
+        }
+    }
+
+    /// The nearest float. Anything too big for an f64 becomes infinite.
+    pub fn to_f64(&self) -> f64 {
+        let mut x = 0.0;
+        for digit in self.mag.iter().rev() {
+            x = x * 4294967296.0 + *digit as f64;
+        }
+        if self.negative {
+            -x
+        } else {
+            x
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.mag.clone())
+    }
+
+    /// Division that truncates towards zero, like integer division does in
+    /// rust. The remainder takes the sign of the dividend. Returns None when
+    /// dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = div_rem_mag(&self.mag, &other.mag);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, q),
+            BigInt::from_parts(self.negative, r),
+        ))
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut acc = BigInt::from_i64(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = &acc * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// The greatest common divisor, which is never negative.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b).unwrap();
+            a = b;
+            b = r;
+        }
+        a
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.mag, &other.mag),
+            (true, true) => cmp_mag(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.mag, &other.mag));
+        }
+        // Signs differ, so subtract the smaller magnitude from the larger and
+        // take the sign of the larger.
+        match cmp_mag(&self.mag, &other.mag) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.mag, &self.mag)),
+            _ => BigInt::from_parts(self.negative, sub_mag(&self.mag, &other.mag)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut out = vec![0u32; self.mag.len() + other.mag.len()];
+        for (i, a) in self.mag.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, b) in other.mag.iter().enumerate() {
+                let cur = out[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                out[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+            out[i + other.mag.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, out)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.mag.clone())
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off 9 decimal digits at a time, least significant first.
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut mag = self.mag.clone();
+        while !mag.is_empty() {
+            let (q, r) = div_small(&mag, 1_000_000_000);
+            chunks.push(r);
+            mag = q;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.last().unwrap())?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim(mut mag: Vec<u32>) -> Vec<u32> {
+    while mag.last() == Some(&0) {
+        mag.pop();
+    }
+    mag
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry: u64 = 0;
+    for i in 0..a.len().max(b.len()) {
+        let cur = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        out.push(cur as u32);
+        carry = cur >> 32;
+    }
+    out.push(carry as u32);
+    trim(out)
+}
+
+/// Subtract magnitudes, where a must be at least as large as b.
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow: i64 = 0;
+    for (i, digit) in a.iter().enumerate() {
+        let mut cur = *digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if cur < 0 {
+            cur += 1 << 32;
+            borrow = 1;
+        }
+        out.push(cur as u32);
+    }
+    trim(out)
+}
+
+/// a * m + c, for single digits m and c.
+fn mul_small(a: &[u32], m: u32, c: u32) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len() + 1);
+    let mut carry = c as u64;
+    for digit in a {
+        let cur = *digit as u64 * m as u64 + carry;
+        out.push(cur as u32);
+        carry = cur >> 32;
+    }
+    out.push(carry as u32);
+    trim(out)
+}
+
+/// Divide by a single digit, giving the quotient and remainder.
+fn div_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut out = vec![0u32; a.len()];
+    let mut rem: u64 = 0;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 32) | a[i] as u64;
+        out[i] = (cur / d as u64) as u32;
+        rem = cur % d as u64;
+    }
+    (trim(out), rem as u32)
+}
+
+/// Long division of magnitudes. Dividing by a single digit is common enough
+/// to be worth doing quickly, otherwise we go one bit at a time.
+fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if b.len() == 1 {
+        let (q, r) = div_small(a, b[0]);
+        return (q, trim(vec![r]));
+    }
+    if cmp_mag(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    let mut q = vec![0u32; a.len()];
+    let mut r: Vec<u32> = Vec::new();
+    for i in (0..a.len() * 32).rev() {
+        // r = r * 2 + the next bit of a
+        r = mul_small(&r, 2, (a[i / 32] >> (i % 32)) & 1);
+        if cmp_mag(&r, b) != Ordering::Less {
+            r = sub_mag(&r, b);
+            q[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (trim(q), r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text, 10).unwrap()
+    }
+
+    #[test]
+    fn round_trip_i64() {
+        for x in [0, 1, -1, 4294967296, i64::MAX, i64::MIN] {
+            assert!(BigInt::from_i64(x).to_i64() == Some(x));
+            assert!(BigInt::from_i64(x).to_string() == x.to_string());
+        }
+        assert!(big("9223372036854775808").to_i64().is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert!((&a + &b).to_string() == "-864197532086419753208641975320");
+        assert!((&a - &b).to_string() == "1111111110111111111011111111100");
+        assert!(
+            (&a * &b).to_string()
+                == "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        let (q, r) = b.div_rem(&a).unwrap();
+        assert!(q.to_string() == "-8");
+        assert!(r.to_string() == "-9000000000900000000090");
+        assert!(big("2").pow(100).to_string() == "1267650600228229401496703205376");
+        assert!(big("12").gcd(&big("-18")) == big("6"));
+        assert!(a.div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(big("-5") < big("3"));
+        assert!(big("-50000000000000000000") < big("-5"));
+        assert!(big("50000000000000000000") > big("5"));
+        assert!(-&BigInt::zero() == BigInt::zero());
+    }
+}
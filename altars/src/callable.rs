@@ -1,6 +1,67 @@
-use crate::{ast::Value, interpreter::Interpreter};
+use std::fmt::{self, Display};
 
+use crate::{
+    ast::Value,
+    interpreter::{Interpreter, RuntimeError},
+    token::Token,
+};
+
+/// Anything that can be called: native functions, incantatios (including
+/// methods bound to an instance) and daemonia, which summon an instance.
 pub trait Callable {
-    fn arity(&self, interpeter: &Interpreter) -> usize;
-    fn call(&self, interpeter: &mut Interpreter, args: Vec<Value>) -> Result<Value, String>;
+    /// The name to use when reporting errors about a call.
+    fn name(&self) -> &str;
+    fn arity(&self, interpeter: &Interpreter) -> Arity;
+    fn call(&self, interpeter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError>;
+
+    /// Call with some of the arguments passed by name. Only incantatios have
+    /// parameter names to match them against, so by default this is an error.
+    fn call_named(
+        &self,
+        interpeter: &mut Interpreter,
+        args: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        if let Some((name, _)) = named.first() {
+            let emsg = format!(
+                "{} does not take named arguments, but was passed {}. Line {}",
+                self.name(),
+                name.lexeme,
+                name.line
+            );
+            return Err(RuntimeError::new("argument", emsg, Some(name.line)));
+        }
+        self.call(interpeter, args)
+    }
+}
+
+/// How many arguments a callable accepts. Parameters with defaults lower the
+/// minimum, and a rest parameter removes the maximum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub fn exactly(n: usize) -> Arity {
+        Arity {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    pub fn accepts(&self, n: usize) -> bool {
+        n >= self.min && self.max.is_none_or(|max| n <= max)
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
 }
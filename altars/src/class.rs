@@ -0,0 +1,154 @@
+//! Classes (daemonia) and the instances that they produce.
+use core::fmt;
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    ast::Value,
+    callable::{Arity, Callable},
+    interpreter::{Interpreter, RuntimeError},
+    token::Token,
+    userfunction::UserFunction,
+};
+
+/// The name of the method that is run when a daemonium is summoned (called).
+pub const INITIALIZER: &str = "initium";
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Class {
+    name: String,
+    superclass: Option<Rc<Class>>,
+    methods: HashMap<String, UserFunction>,
+}
+
+impl Class {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<Class>>,
+        methods: HashMap<String, UserFunction>,
+    ) -> Class {
+        Class {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    /// Find a method on this daemonium, walking up the chain of parents if we
+    /// don't define it ourselves.
+    pub fn find_method(&self, name: &str) -> Option<UserFunction> {
+        match self.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => match &self.superclass {
+                Some(parent) => parent.find_method(name),
+                None => None,
+            },
+        }
+    }
+}
+
+/// Calling a daemonium summons a new instance of it. The call is implemented
+/// on the Rc so that the instance can hold on to it's class.
+impl Callable for Rc<Class> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Summoning a daemonium takes the same arguments as it's initializer.
+    fn arity(&self, interpreter: &Interpreter) -> Arity {
+        match self.find_method(INITIALIZER) {
+            Some(initializer) => initializer.arity(interpreter),
+            None => Arity::exactly(0),
+        }
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.call_named(interpreter, args, Vec::new())
+    }
+
+    /// If the class declares an initializer then we run it against the new
+    /// instance with the arguments of the call.
+    fn call_named(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        let instance = Rc::new(RefCell::new(Instance::new(self.clone())));
+        if let Some(initializer) = self.find_method(INITIALIZER) {
+            initializer
+                .bind(instance.clone())
+                .call_named(interpreter, args, named)?;
+        }
+        Ok(Value::Instance(instance))
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Daemonium({})", self.name)
+    }
+}
+
+/// A summoned daemonium. Instances are shared by reference, so every binding
+/// that refers to the same instance sees the same fields.
+pub struct Instance {
+    class: Rc<Class>,
+    fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Instance {
+        Instance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Look up a property on an instance. Fields shadow methods, and any method
+    /// we find is bound to the instance so that `anima` refers to it.
+    pub fn get(instance: &Rc<RefCell<Instance>>, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(val) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(val.clone());
+        }
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(m) => Ok(Value::UserFn(m.bind(instance.clone()))),
+            None => {
+                let emsg = format!(
+                    "Undefined property {} on {}. Line {}",
+                    name.lexeme,
+                    instance.borrow(),
+                    name.line
+                );
+                Err(RuntimeError::new("name", emsg, Some(name.line)))
+            }
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, val: Value) {
+        self.fields.insert(name.lexeme.clone(), val);
+    }
+}
+
+/// Instances are compared by identity rather than by their contents.
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+// Fields can (indirectly) refer back to the instance that holds them, so we
+// only print the field names to avoid chasing cycles.
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut fields: Vec<&String> = self.fields.keys().collect();
+        fields.sort();
+        write!(f, "Instance({} {:?})", self.class.name, fields)
+    }
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Instance({})", self.class.name)
+    }
+}
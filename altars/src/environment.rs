@@ -1,98 +1,123 @@
 //! State, Binding, Scope and name tracking.
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use crate::{ast::Value, token::Token};
+use crate::{ast::Value, interpreter::RuntimeError, token::Token};
 
+/// A name bound in a scope. Bindings made with constans (and the builtins)
+/// aren't mutable, so they can't be assigned to, or declared again in the same
+/// scope.
 #[derive(Clone, Debug)]
-pub struct Environment {
-    pub parent: Option<Box<Environment>>,
-    pub values: HashMap<String, Option<Value>>,
+pub struct Binding {
+    pub value: Option<Value>,
+    pub mutable: bool,
 }
 
-impl Environment {
-    pub fn new() -> Environment {
-        Environment {
-            parent: None,
-            values: HashMap::new(),
-        }
+impl Binding {
+    fn new(value: Option<Value>, mutable: bool) -> Binding {
+        Binding { value, mutable }
     }
+}
 
-    pub fn from(e: Environment) -> Environment {
-        Environment {
-            parent: None,
-            values: e.values.clone(),
-        }
-    }
+/// A single scope. Scopes are shared between everything that can see them,
+/// (blocks, function calls, and the functions that close over them) so they
+/// live behind an `Rc<RefCell<_>>`, and each one points at it's enclosing scope.
+#[derive(Clone, Debug)]
+pub struct Environment {
+    pub parent: Option<Rc<RefCell<Environment>>>,
+    pub values: HashMap<String, Binding>,
+}
 
+impl Environment {
+    /// Everything in the table is bound as a constant, since this is how the
+    /// builtins get in.
     pub fn from_ht(other: HashMap<String, Option<Value>>) -> Environment {
         Environment {
             parent: None,
-            values: other.clone(),
+            values: Environment::constants(other),
         }
     }
 
-    pub fn with_parent(parent: Environment) -> Environment {
+    pub fn with_parent(parent: Rc<RefCell<Environment>>) -> Environment {
         Environment {
-            parent: Some(Box::new(parent)),
+            parent: Some(parent),
             values: HashMap::new(),
         }
     }
 
-    pub fn from_with_parent(e: Environment, parent: Environment) -> Environment {
-        Environment {
-            parent: Some(Box::new(parent)),
-            values: e.values.clone(),
-        }
+    fn constants(table: HashMap<String, Option<Value>>) -> HashMap<String, Binding> {
+        table
+            .into_iter()
+            .map(|(name, val)| (name, Binding::new(val, false)))
+            .collect()
     }
 
-    pub fn from_ht_with_parent(
-        other: HashMap<String, Option<Value>>,
-        parent: Environment,
-    ) -> Environment {
-        Environment {
-            parent: Some(Box::new(parent)),
-            values: other.clone(),
-        }
+    /// Bind a name in this scope, whatever was there before. This is for the
+    /// interpreter's own bindings, like parameters and anima, anything the
+    /// ritual declares itself goes through [Environment::declare].
+    pub fn define(&mut self, name: String, val: Option<Value>) {
+        self.values.insert(name, Binding::new(val, true));
     }
 
-    pub fn define(&mut self, name: String, val: Option<Value>) {
-        self.values.insert(name, val);
+    /// Declare a name in this scope. Redeclaring a mutable name just replaces
+    /// it, but a constant in the same scope can't be declared over.
+    pub fn declare(
+        &mut self,
+        name: &Token,
+        val: Option<Value>,
+        mutable: bool,
+    ) -> Result<(), RuntimeError> {
+        if let Some(Binding { mutable: false, .. }) = self.values.get(&name.lexeme) {
+            let emsg = format!(
+                "Can't declare {} again, since it's a constant. Line {}",
+                name.lexeme, name.line
+            );
+            return Err(RuntimeError::new("constant", emsg, Some(name.line)));
+        }
+        self.values
+            .insert(name.lexeme.clone(), Binding::new(val, mutable));
+        Ok(())
     }
 
+    /// Look a name up in this scope, and then in each enclosing scope in turn.
     pub fn get(&self, name: Token) -> Option<Value> {
         let symbol = name.lexeme.clone();
         match self.values.get(&symbol) {
-            Some(val) => {
-                return val.clone();
-            }
-            None => {
-                return None;
+            Some(binding) => {
+                return binding.value.clone();
             }
+            None => match &self.parent {
+                Some(parent) => parent.borrow().get(name),
+                None => {
+                    return None;
+                }
+            },
         }
     }
 
-    pub fn assign(&mut self, name: Token, val: &Value) -> Result<(), String> {
-        if self.values.contains_key(&name.lexeme) {
-            self.define(name.lexeme, Some(val.clone()));
+    /// Assign to the nearest enclosing binding of name.
+    pub fn assign(&mut self, name: Token, val: &Value) -> Result<(), RuntimeError> {
+        if let Some(binding) = self.values.get_mut(&name.lexeme) {
+            if !binding.mutable {
+                let emsg = format!(
+                    "Can't assign to {}, since it's a constant. Line {}",
+                    name.lexeme, name.line
+                );
+                return Err(RuntimeError::new("constant", emsg, Some(name.line)));
+            }
+            binding.value = Some(val.clone());
             return Ok(());
+        } else if let Some(parent) = &self.parent {
+            return parent.borrow_mut().assign(name, val);
         } else {
             let emsg = format!(
-                "Error: Tried to assign value {} to undefined variable {}",
+                "Error: Tried to assign value {} to undefined variable {}. Line {}",
                 name.lexeme,
-                val.clone()
+                val.clone(),
+                name.line
             );
-            return Err(emsg);
+            return Err(RuntimeError::new("name", emsg, Some(name.line)));
         }
     }
-
-    pub fn merge_defs(&self, other: HashMap<String, Option<Value>>) -> Environment {
-        let mut new = self.clone();
-        new.values.extend(other);
-        new
-    }
-
-    pub fn merge_envs(&self, other: Environment) -> Environment {
-        let other = other.values.clone();
-        self.merge_defs(other)
-    }
 }
@@ -1,19 +1,162 @@
 //! A Tree Walk Interpreter for the Daemonica Language
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use fxhash::FxHashMap;
+
 use crate::ast::ASTNode;
 use crate::ast::Expr;
+use crate::ast::Param;
 use crate::ast::Stmt;
 use crate::ast::Value;
+use crate::class::{Class, Instance};
 use crate::environment::Environment;
 use crate::literals::Literal;
+use crate::mapkey::MapKey;
+use crate::module::Module;
 use crate::nativefn;
+use crate::numeric;
+use crate::parser::Parser;
+use crate::scanner::Scanner;
 use crate::token::Token;
 use crate::tokentype::TokenType;
 use crate::userfunction::UserFunction;
 
 #[derive(Debug)]
 pub struct Interpreter {
-    pub environment: Environment,
-    pub retval: Option<Value>,
+    pub environment: Rc<RefCell<Environment>>,
+    /// Every module that has been loaded, by it's path, so that each file is
+    /// only ever run once.
+    modules: HashMap<PathBuf, Rc<Module>>,
+    /// The files that are being run right now. The last one is the file that
+    /// invocabo paths are relative to, and anything else in here is still
+    /// part way through loading, so loading it again would be a cycle.
+    files: Vec<PathBuf>,
+}
+
+/// Statements can stop executing early for more reasons than just errors.
+/// Each of these unwinds through [Interpreter::interpret_stmt] and
+/// [Interpreter::interpret_block] until something handles it. Errors go all the
+/// way to the top, whereas a Return is caught by the call that it belongs to.
+#[derive(Debug)]
+pub enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+    Break,
+    Continue,
+}
+
+impl Unwind {
+    /// Anything that unwinds all the way to the top level is an error.
+    pub fn into_error(self) -> RuntimeError {
+        let emsg = match self {
+            Unwind::Error(e) => return e,
+            Unwind::Return(_) => String::from("Used beneficium outside of an incantatio"),
+            Unwind::Break => String::from("Used frange outside of a loop"),
+            Unwind::Continue => String::from("Used perge outside of a loop"),
+        };
+        RuntimeError::from(emsg)
+    }
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(e: RuntimeError) -> Self {
+        Unwind::Error(e)
+    }
+}
+
+impl From<String> for Unwind {
+    fn from(e: String) -> Self {
+        Unwind::Error(RuntimeError::from(e))
+    }
+}
+
+/// An error raised while running a ritual, which is what a capere catches.
+///
+/// The kind says what sort of error it was. Errors raised with iacere are
+/// an "error" unless they say otherwise, and the interpreter raises "name",
+/// "constant", "type", "arithmetic", "index", "argument", "io" and "import"
+/// errors, or "runtime" for anything else.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub kind: String,
+    /// The line the error was raised on, if we know it.
+    pub line: Option<usize>,
+    /// What gets reported if nothing catches the error.
+    report: String,
+}
+
+impl RuntimeError {
+    /// An error raised by the interpreter. The message is reported as it is,
+    /// so it should already say which line it happened on.
+    pub fn new(kind: &str, message: String, line: Option<usize>) -> RuntimeError {
+        RuntimeError {
+            report: message.clone(),
+            message,
+            kind: kind.to_string(),
+            line,
+        }
+    }
+
+    /// An error from something that didn't know which line it was on, so
+    /// the line is added to the end of it's message.
+    pub fn at(kind: &str, message: String, line: usize) -> RuntimeError {
+        RuntimeError::new(kind, format!("{}. Line {}", message, line), Some(line))
+    }
+
+    /// An error raised with iacere.
+    pub fn thrown(message: String, kind: String, line: usize) -> RuntimeError {
+        RuntimeError {
+            report: format!("{}: {}. Line {}", kind, message, line),
+            message,
+            kind,
+            line: Some(line),
+        }
+    }
+
+    /// Errors that don't know where they happened, like those from a native
+    /// function, take the line of whatever raised them instead.
+    pub fn with_line(mut self, line: usize) -> RuntimeError {
+        self.line = self.line.or(Some(line));
+        self
+    }
+
+    /// The map that a capere binds, with the message, the kind, and the line,
+    /// or nihil if we don't know it.
+    pub fn to_value(&self) -> Value {
+        let line = match self.line {
+            Some(line) => Value::Integer(line as i64),
+            None => Value::Empty,
+        };
+        let mut fields: FxHashMap<MapKey, Value> = FxHashMap::default();
+        fields.insert(
+            MapKey::String(String::from("message")),
+            Value::String(self.message.clone()),
+        );
+        fields.insert(
+            MapKey::String(String::from("kind")),
+            Value::String(self.kind.clone()),
+        );
+        fields.insert(MapKey::String(String::from("line")), line);
+        Value::Map(Rc::new(RefCell::new(fields)))
+    }
+}
+
+impl From<String> for RuntimeError {
+    fn from(e: String) -> Self {
+        RuntimeError::new("runtime", e, None)
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report)
+    }
 }
 
 //impl<T> Visitor<T> for Interpreter {
@@ -21,23 +164,33 @@ impl Interpreter {
     pub fn new() -> Interpreter {
         let environment = Environment::from_ht(nativefn::generate_native_functions());
         return Interpreter {
-            environment,
-            retval: None,
+            environment: Rc::new(RefCell::new(environment)),
+            modules: HashMap::new(),
+            files: Vec::new(),
         };
     }
 
+    /// An interpreter for running the ritual in the given file, so that it can
+    /// load other rituals relative to itself.
+    pub fn with_file(path: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        let path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        interpreter.files.push(path);
+        interpreter
+    }
+
     pub fn interpret(&mut self, nodes: Vec<ASTNode>) -> Result<Vec<Value>, String> {
         let mut results: Vec<Value> = Vec::new();
         for node in nodes {
             match node {
-                ASTNode::StmtNode(x) => match self.interpret_stmt(x) {
+                ASTNode::StmtNode(x) => match self.interpret_stmt(x).map_err(Unwind::into_error) {
                     Ok(y) => {
                         results.push(y);
                     }
                     Err(y) => {
                         println!("Encountered an error {}", y);
                         println!("Environment at this state was {:#?}", self.environment);
-                        return Err(y);
+                        return Err(y.to_string());
                     }
                 },
                 ASTNode::ExprNode(x) => match self.interpret_expr(x) {
@@ -47,7 +200,7 @@ impl Interpreter {
                     Err(y) => {
                         println!("Encountered an error {}", y);
                         println!("Environment at this state was {:#?}", self.environment);
-                        return Err(y);
+                        return Err(y.to_string());
                     }
                 },
             }
@@ -56,7 +209,21 @@ impl Interpreter {
         return Ok(results);
     }
 
-    pub fn interpret_expr(&mut self, expr: Expr) -> Result<Value, String> {
+    /// Evaluate an expression in some other scope, such as the frame of a call
+    /// when we evaluate the default value of a parameter.
+    pub fn interpret_expr_in(
+        &mut self,
+        expr: Expr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<Value, RuntimeError> {
+        let prevenv = self.environment.clone();
+        self.environment = env;
+        let res = self.interpret_expr(expr);
+        self.environment = prevenv;
+        res
+    }
+
+    pub fn interpret_expr(&mut self, expr: Expr) -> Result<Value, RuntimeError> {
         match expr {
             Expr::Assign(name, value) => {
                 return self.interpret_assignment(name, *value);
@@ -64,33 +231,68 @@ impl Interpreter {
             Expr::Binary(left, oper, right) => {
                 return self.interpret_binary(*left, oper, *right);
             }
-            Expr::Call(callee, paren, args) => self.interpret_call(*callee, paren, args),
+            Expr::Call(callee, paren, args, named) => {
+                self.interpret_call(*callee, paren, args, named)
+            }
+            Expr::Conditional(cond, then, otherwise) => {
+                self.interpret_conditional(*cond, *then, *otherwise)
+            }
+            Expr::Get(object, name) => self.interpret_get(*object, name),
             Expr::Grouping(expression) => {
                 return self.interpret_expr(*expression);
             }
+            Expr::Index(object, bracket, index) => self.interpret_index(*object, bracket, *index),
+            Expr::Lambda(keyword, params, body) => Ok(Value::UserFn(UserFunction::new(
+                keyword,
+                body,
+                params,
+                self.environment.clone(),
+            ))),
+            Expr::Interpolate(_, parts) => self.interpret_interpolate(parts),
+            Expr::List(items) => self.interpret_list(items),
+            Expr::Map(brace, pairs) => self.interpret_map(brace, pairs),
             Expr::Literal(value) => {
                 return self.interpret_literal(value);
             }
             Expr::Logic(left, operator, right) => {
                 return self.interpret_logical(*left, operator, *right);
             }
+            Expr::Set(object, name, value) => self.interpret_set(*object, name, *value),
+            Expr::SetIndex(object, bracket, index, value) => {
+                self.interpret_set_index(*object, bracket, *index, *value)
+            }
+            Expr::Self_(keyword) => self.interpret_var_expr(keyword),
+            Expr::Super(keyword, method) => self.interpret_super(keyword, method),
             Expr::Unary(operator, right) => {
                 return self.interpret_unary(operator, *right);
             }
+            Expr::Update(target, oper, value) => self.interpret_update(*target, oper, *value),
             Expr::Variable(name) => self.interpret_var_expr(name),
         }
     }
 
-    pub fn interpret_stmt(&mut self, stmt: Stmt) -> Result<Value, String> {
+    pub fn interpret_stmt(&mut self, stmt: Stmt) -> Result<Value, Unwind> {
         match stmt {
-            Stmt::Block(stmts) => self.interpret_block(stmts, self.environment.clone()),
-            Stmt::Expression(expr) => self.interpret_expr(expr),
-            Stmt::Function(name, params, body) => self.interpret_function(name, body, params),
+            Stmt::Block(stmts) => {
+                let scope = Environment::with_parent(self.environment.clone());
+                self.interpret_block(stmts, Rc::new(RefCell::new(scope)))
+            }
+            Stmt::Break(_) => Err(Unwind::Break),
+            Stmt::Class(name, superclass, methods) => {
+                Ok(self.interpret_class(name, superclass, methods)?)
+            }
+            Stmt::Continue(_) => Err(Unwind::Continue),
+            Stmt::Expression(expr) => Ok(self.interpret_expr(expr)?),
+            Stmt::Function(name, params, body) => Ok(self.interpret_function(name, body, params)?),
             Stmt::If(cond, thenb, elseb) => self.interpret_if(cond, thenb, elseb),
             Stmt::Return(tok, val) => self.interpret_return(tok, val),
-            Stmt::Var(tok, initializer) => self.interpret_var_stmt(tok, initializer),
-            Stmt::While(cond, body) => self.interpret_while(&cond, body),
-            Stmt::Print(expr) => self.interpret_print(expr),
+            Stmt::Import(keyword, path) => Ok(self.interpret_import(keyword, path)?),
+            Stmt::Throw(keyword, val) => self.interpret_throw(keyword, val),
+            Stmt::Try(body, name, handler) => self.interpret_try(body, name, handler),
+            Stmt::Var(tok, initializer) => Ok(self.interpret_var_stmt(tok, initializer)?),
+            Stmt::Const(tok, initializer) => Ok(self.interpret_const_stmt(tok, initializer)?),
+            Stmt::While(cond, body, increment) => self.interpret_while(&cond, body, increment),
+            Stmt::Print(expr) => Ok(self.interpret_print(expr)?),
         }
     }
 
@@ -98,35 +300,228 @@ impl Interpreter {
         &mut self,
         name: Token,
         body: Vec<Stmt>,
-        params: Vec<Token>,
-    ) -> Result<Value, String> {
-        let fun = Value::UserFn(UserFunction::new(name.clone(), body, params));
-        self.environment.define(name.lexeme, Some(fun.clone()));
+        params: Vec<Param>,
+    ) -> Result<Value, RuntimeError> {
+        let fun = Value::UserFn(UserFunction::new(
+            name.clone(),
+            body,
+            params,
+            self.environment.clone(),
+        ));
+        self.environment
+            .borrow_mut()
+            .declare(&name, Some(fun.clone()), true)?;
         Ok(Value::Empty)
     }
 
-    fn interpret_return(&mut self, tok: Token, val: Option<Expr>) -> Result<Value, String> {
-        match val {
-            Some(x) => {
-                let evaled = self.interpret_expr(x)?;
-                let val: Value = evaled.into();
-                self.retval = Some(val.clone());
-                return Ok(val.clone());
+    /// Collect the methods of a daemonium, and bind the resulting class to its name.
+    /// If the daemonium has a parent, then it must also be a daemonium.
+    fn interpret_class(
+        &mut self,
+        name: Token,
+        superclass: Option<Expr>,
+        methods: Vec<Stmt>,
+    ) -> Result<Value, RuntimeError> {
+        let superclass = match superclass {
+            Some(parent) => match self.interpret_expr(parent.clone())? {
+                Value::Class(c) => Some(c),
+                other => {
+                    let emsg = format!(
+                        "Daemonium {} can only inherit from another daemonium, but {} is {}. Line {}",
+                        name.lexeme, parent, other, name.line
+                    );
+                    return Err(RuntimeError::new("type", emsg, Some(name.line)));
+                }
+            },
+            None => None,
+        };
+        // Methods of a daemonium with a parent close over an extra scope that
+        // binds cognatio to that parent.
+        let closure = match &superclass {
+            Some(parent) => {
+                let mut scope = Environment::with_parent(self.environment.clone());
+                scope.define(String::from("cognatio"), Some(Value::Class(parent.clone())));
+                Rc::new(RefCell::new(scope))
             }
-            None => {
-                self.retval = None;
-                return Ok(Value::Empty);
+            None => self.environment.clone(),
+        };
+        let mut table: HashMap<String, UserFunction> = HashMap::new();
+        for method in methods {
+            if let Stmt::Function(mname, params, body) = method {
+                let fun = UserFunction::new(mname.clone(), body, params, closure.clone());
+                table.insert(mname.lexeme, fun);
+            }
+        }
+        let class = Value::Class(Rc::new(Class::new(name.lexeme.clone(), superclass, table)));
+        self.environment
+            .borrow_mut()
+            .declare(&name, Some(class), true)?;
+        Ok(Value::Empty)
+    }
+
+    /// Returning unwinds out of whatever blocks and loops we are inside of,
+    /// until it reaches the call to the function that we are returning from.
+    fn interpret_return(&mut self, _tok: Token, val: Option<Expr>) -> Result<Value, Unwind> {
+        let val = match val {
+            Some(x) => self.interpret_expr(x)?,
+            None => Value::Empty,
+        };
+        Err(Unwind::Return(val))
+    }
+
+    /// Load a module, and bind it to the name of it's file, without the
+    /// extension.
+    fn interpret_import(&mut self, keyword: Token, path: String) -> Result<Value, RuntimeError> {
+        let module = self.load_module(&keyword, &path)?;
+        let name = Token::new(
+            TokenType::Identifier,
+            module.name.clone(),
+            Literal::Empty,
+            keyword.line,
+        );
+        self.environment
+            .borrow_mut()
+            .declare(&name, Some(Value::Module(module)), true)?;
+        Ok(Value::Empty)
+    }
+
+    /// Find, and if we haven't already, run the ritual at path. Paths are
+    /// relative to the file doing the loading, or to the working directory
+    /// if there isn't one, like in the repl.
+    fn load_module(&mut self, keyword: &Token, path: &str) -> Result<Rc<Module>, RuntimeError> {
+        let base = match self.files.last().and_then(|f| f.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::new(),
+        };
+        let path = match fs::canonicalize(base.join(path)) {
+            Ok(p) => p,
+            Err(e) => {
+                let emsg = format!("Unable to invocabo {}. {}. Line {}", path, e, keyword.line);
+                return Err(RuntimeError::new("import", emsg, Some(keyword.line)));
+            }
+        };
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.files.iter().position(|f| *f == path) {
+            let chain: Vec<String> = self.files[start..]
+                .iter()
+                .chain([&path])
+                .map(|f| Interpreter::file_name(f))
+                .collect();
+            let emsg = format!(
+                "Rituals can't invocabo each other in a cycle: {}. Line {}",
+                chain.join(" -> "),
+                keyword.line
+            );
+            return Err(RuntimeError::new("import", emsg, Some(keyword.line)));
+        }
+        let name = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(name) if Scanner::is_identifier(name) => name.to_string(),
+            _ => {
+                let emsg = format!(
+                    "Unable to invocabo {}, since it's name can't be used as an identifier. Line {}",
+                    path.display(),
+                    keyword.line
+                );
+                return Err(RuntimeError::new("import", emsg, Some(keyword.line)));
+            }
+        };
+        let src = match fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(e) => {
+                let emsg = format!(
+                    "Unable to invocabo {}. {}. Line {}",
+                    path.display(),
+                    e,
+                    keyword.line
+                );
+                return Err(RuntimeError::new("import", emsg, Some(keyword.line)));
+            }
+        };
+        let stmts: Vec<Stmt> = Parser::parse(Scanner::scan(src))
+            .into_iter()
+            .map(|node| match node {
+                ASTNode::StmtNode(stmt) => stmt,
+                ASTNode::ExprNode(expr) => Stmt::Expression(expr),
+            })
+            .collect();
+
+        // Modules get a fresh global scope of their own, so that they can't
+        // see (or change) anything the loading ritual has.
+        let scope = Environment::from_ht(nativefn::generate_native_functions());
+        let scope = Rc::new(RefCell::new(scope));
+        self.files.push(path.clone());
+        let result = self.interpret_block(stmts, scope.clone());
+        self.files.pop();
+        result.map_err(Unwind::into_error)?;
+
+        let module = Rc::new(Module::new(name, path.clone(), scope));
+        self.modules.insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// Raise an error. A map is raised as the error itself, so that a caught
+    /// error can be raised again as it was, and anything else becomes the
+    /// message of a new error.
+    fn interpret_throw(&mut self, keyword: Token, val: Expr) -> Result<Value, Unwind> {
+        let (message, kind, line) = match self.interpret_expr(val)? {
+            Value::Map(m) => {
+                let fields = m.borrow();
+                let get = |key: &str| fields.get(&MapKey::String(key.to_string())).cloned();
+                let message = match get("message") {
+                    Some(message) => message.to_string(),
+                    None => {
+                        let emsg = format!(
+                            "Errors raised with iacere need a message. Line {}",
+                            keyword.line
+                        );
+                        return Err(RuntimeError::new("type", emsg, Some(keyword.line)).into());
+                    }
+                };
+                let kind = get("kind").map_or(String::from("error"), |k| k.to_string());
+                let line = match get("line") {
+                    Some(Value::Integer(line)) if line >= 0 => line as usize,
+                    _ => keyword.line,
+                };
+                (message, kind, line)
+            }
+            other => (other.to_string(), String::from("error"), keyword.line),
+        };
+        Err(RuntimeError::thrown(message, kind, line).into())
+    }
+
+    /// Run the body of a conare, and if it fails, run the capere with the error
+    /// bound in it's own scope. Only errors are caught, anything else that
+    /// unwinds (beneficium, frange, perge) passes straight through.
+    fn interpret_try(
+        &mut self,
+        body: Vec<Stmt>,
+        name: Token,
+        handler: Vec<Stmt>,
+    ) -> Result<Value, Unwind> {
+        let scope = Environment::with_parent(self.environment.clone());
+        match self.interpret_block(body, Rc::new(RefCell::new(scope))) {
+            Err(Unwind::Error(err)) => {
+                let mut scope = Environment::with_parent(self.environment.clone());
+                scope.define(name.lexeme, Some(err.to_value()));
+                self.interpret_block(handler, Rc::new(RefCell::new(scope)))
             }
+            other => other,
         }
     }
 
-    fn interpret_print(&mut self, expr: Expr) -> Result<Value, String> {
+    fn interpret_print(&mut self, expr: Expr) -> Result<Value, RuntimeError> {
         let val = self.interpret_expr(expr)?;
         println!("{}", val);
         return Ok(Value::Empty);
     }
 
-    pub fn interpret_block(&mut self, stmts: Vec<Stmt>, env: Environment) -> Result<Value, String> {
+    pub fn interpret_block(
+        &mut self,
+        stmts: Vec<Stmt>,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<Value, Unwind> {
         let prevenv = self.environment.clone();
         self.environment = env;
         for stmt in stmts {
@@ -138,6 +533,8 @@ impl Interpreter {
                 }
             }
         }
+        // Leaving the block discards it's scope.
+        self.environment = prevenv;
         Ok(Value::Empty)
     }
 
@@ -145,18 +542,45 @@ impl Interpreter {
         &mut self,
         tok: Token,
         initializer: Option<Expr>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, RuntimeError> {
         let value = match initializer {
-            Some(x) => Some(self.interpret_expr(x).unwrap()),
+            Some(x) => Some(self.interpret_expr(x)?),
             None => None,
         };
-        self.environment.define(tok.lexeme, value);
+        self.environment.borrow_mut().declare(&tok, value, true)?;
         return Ok(Value::Empty);
     }
 
-    fn interpret_while(&mut self, cond: &Expr, body: Box<Stmt>) -> Result<Value, String> {
+    fn interpret_const_stmt(
+        &mut self,
+        tok: Token,
+        initializer: Expr,
+    ) -> Result<Value, RuntimeError> {
+        let value = self.interpret_expr(initializer)?;
+        self.environment
+            .borrow_mut()
+            .declare(&tok, Some(value), false)?;
+        Ok(Value::Empty)
+    }
+
+    /// Loops stop early on frange, and skip straight to the next iteration on
+    /// perge. Either way the increment of an enim loop still runs before the
+    /// condition is checked again.
+    fn interpret_while(
+        &mut self,
+        cond: &Expr,
+        body: Box<Stmt>,
+        increment: Option<Expr>,
+    ) -> Result<Value, Unwind> {
         while Interpreter::is_truthy(self.interpret_expr(cond.clone())?) {
-            self.interpret_stmt(*body.clone())?;
+            match self.interpret_stmt(*body.clone()) {
+                Ok(_) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(other) => return Err(other),
+            }
+            if let Some(inc) = &increment {
+                self.interpret_expr(inc.clone())?;
+            }
         }
         Ok(Value::Empty)
     }
@@ -166,7 +590,7 @@ impl Interpreter {
         cond: Expr,
         thenb: Box<Stmt>,
         elseb: Box<Option<Stmt>>,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, Unwind> {
         // If our condition is truthy, evaluate the then branch
         if Interpreter::is_truthy(self.interpret_expr(cond)?) {
             return self.interpret_stmt(*thenb);
@@ -182,90 +606,55 @@ impl Interpreter {
         }
     }
 
-    fn interpret_assignment(&mut self, name: Token, value: Expr) -> Result<Value, String> {
+    fn interpret_assignment(&mut self, name: Token, value: Expr) -> Result<Value, RuntimeError> {
         let val = self.interpret_expr(value)?;
-        self.environment.assign(name, &val)?;
+        self.environment.borrow_mut().assign(name, &val)?;
         Ok(val)
     }
 
-    fn interpret_binary(&mut self, left: Expr, oper: Token, right: Expr) -> Result<Value, String> {
+    fn interpret_binary(
+        &mut self,
+        left: Expr,
+        oper: Token,
+        right: Expr,
+    ) -> Result<Value, RuntimeError> {
         let left = self.interpret_expr(left)?;
         let right = self.interpret_expr(right)?;
+        self.apply_binary(left, oper, right)
+    }
 
-        let l = match left {
-            Value::Number(x) => x,
+    /// Apply a binary operator to two values that have already been evaluated.
+    fn apply_binary(
+        &mut self,
+        left: Value,
+        oper: Token,
+        right: Value,
+    ) -> Result<Value, RuntimeError> {
+        match oper.ttype {
+            TokenType::EqualEqual => {
+                return Ok(Value::Bool(self.is_equal(left, right)));
+            }
+            TokenType::BangEqual => {
+                return Ok(Value::Bool(!self.is_equal(left, right)));
+            }
+            _ => {}
+        }
+
+        match left {
             // String concatenation is done with + because that's what everything
             // else uses. As such we need to handle this without tying ourselves
             // in knots or making rustc angry about types, so we do it here.
             Value::String(ref x) => match right {
-                Value::String(y) => {
+                Value::String(y) if oper.ttype == TokenType::Plus => {
                     return Ok(Value::String(format!("{}{}", x, y)));
                 }
                 _ => {
-                    let emsg = format!("Attempted to concatenate values of incompatable types. left: {:?} right {:?}", x, left);
-                    return Err(emsg);
+                    let emsg = format!("Attempted to apply {} to the string {:?} and {}, which is invalid. Line {}",
+                                       oper.lexeme, x, right, oper.line);
+                    return Err(RuntimeError::new("type", emsg, Some(oper.line)));
                 }
             },
-            _ => {
-                let emsg = format!("Attempted to apply a binary operation {:?} to {:?} and {:?} but lvalue ({:?}) is not a number!",
-                                   oper, left, right, left);
-                return Err(emsg);
-            }
-        };
-
-        let r = match right {
-            Value::Number(x) => x,
-            _ => {
-                let emsg = format!("Attempted to apply a binary operation {:?} to {:?} and {:?} but rvalue ({:?}) is not a number!",
-                                   oper, left, right, right);
-                return Err(emsg);
-            }
-        };
-
-        match oper.ttype {
-            TokenType::Minus => {
-                return Ok(Value::Number(l - r));
-            }
-            TokenType::Slash => {
-                if l == 0.0 || r == 0.0 {
-                    let emsg =
-                        format!("Attempted to divide by zero!. Expression was {} / {}", l, r);
-                    return Err(emsg);
-                } else {
-                    return Ok(Value::Number(l / r));
-                }
-            }
-            TokenType::Star => {
-                return Ok(Value::Number(l * r));
-            }
-            TokenType::Plus => {
-                return Ok(Value::Number(l + r));
-            }
-            TokenType::EqualEqual => {
-                return Ok(Value::Bool(self.is_equal(left, right)));
-            }
-            TokenType::BangEqual => {
-                return Ok(Value::Bool(!self.is_equal(left, right)));
-            }
-            TokenType::Greater => {
-                return Ok(Value::Bool(l > r));
-            }
-            TokenType::GreaterEqual => {
-                return Ok(Value::Bool(l >= r));
-            }
-            TokenType::Less => {
-                return Ok(Value::Bool(l < r));
-            }
-            TokenType::LessEqual => {
-                return Ok(Value::Bool(l <= r));
-            }
-            _ => {
-                let msg = format!(
-                    "Attempted to evaluate an invalid binary expression. {:?} {:?} {:?}",
-                    left, oper, right
-                );
-                return Err(msg);
-            }
+            _ => numeric::binary(&oper, &left, &right),
         }
     }
 
@@ -279,81 +668,187 @@ impl Interpreter {
     /// scribo a;
     /// ```
     /// In this case, a will contain the current unix time stamp.
+    /// Any expression can be called, so long as it evaluates to something
+    /// callable: a function, a method bound to it's instance or a daemonium.
     fn interpret_call(
         &mut self,
         callee: Expr,
-        _paren: Token,
+        paren: Token,
         args: Vec<Expr>,
-    ) -> Result<Value, String> {
+        named: Vec<(Token, Expr)>,
+    ) -> Result<Value, RuntimeError> {
         let evaled = match callee {
-            Expr::Variable(ref v) => match self.environment.get(v.clone()) {
+            Expr::Variable(ref v) => match self.environment.borrow().get(v.clone()) {
                 Some(f) => f,
                 None => {
-                    let emsg = format!("Tried to call undefined function {}", v.lexeme);
-                    return Err(emsg);
+                    let emsg = format!(
+                        "Tried to call undefined function {}. Line {}",
+                        v.lexeme, v.line
+                    );
+                    return Err(RuntimeError::new("name", emsg, Some(v.line)));
                 }
             },
+            _ => self.interpret_expr(callee)?,
+        };
+        let mut evaledArgs: Vec<Value> = Vec::new();
+        for arg in args {
+            evaledArgs.push(self.interpret_expr(arg)?);
+        }
+        let mut evaled_named: Vec<(Token, Value)> = Vec::new();
+        for (name, arg) in named {
+            evaled_named.push((name, self.interpret_expr(arg)?));
+        }
+        let callable = match evaled.as_callable() {
+            Some(callable) => callable,
+            None => {
+                let emsg = format!(
+                    "Tried to call {}, which is neither a function nor a daemonium. Line {}",
+                    evaled, paren.line
+                );
+                return Err(RuntimeError::new("type", emsg, Some(paren.line)));
+            }
+        };
+        let arity = callable.arity(self);
+        let given = evaledArgs.len() + evaled_named.len();
+        if !arity.accepts(given) {
+            let emsg = format!(
+                "{} expects {} arguments but was called with {}. Line {}",
+                callable.name(),
+                arity,
+                given,
+                paren.line
+            );
+            return Err(RuntimeError::new("argument", emsg, Some(paren.line)));
+        }
+        // Natives don't know which line they were called from, so their
+        // errors are raised at the call.
+        callable
+            .call_named(self, evaledArgs, evaled_named)
+            .map_err(|e| e.with_line(paren.line))
+    }
+
+    /// Property access. Only instances of a daemonium have properties.
+    fn interpret_get(&mut self, object: Expr, name: Token) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        Interpreter::get_property(object, &name)
+    }
+
+    fn get_property(object: Value, name: &Token) -> Result<Value, RuntimeError> {
+        match object {
+            Value::Instance(instance) => Instance::get(&instance, name),
+            Value::Module(module) => module.get(name),
+            other => {
+                let emsg = format!(
+                    "Tried to access property {} on {}, but only instances have properties. Line {}",
+                    name.lexeme, other, name.line
+                );
+                Err(RuntimeError::new("type", emsg, Some(name.line)))
+            }
+        }
+    }
+
+    /// Look up a method on the parent of the daemonium whose method we are
+    /// currently running, and bind it to the current instance.
+    fn interpret_super(&mut self, keyword: Token, method: Token) -> Result<Value, RuntimeError> {
+        let superclass = match self.environment.borrow().get(keyword.clone()) {
+            Some(Value::Class(c)) => c,
             _ => {
                 let emsg = format!(
-                    "Tried to call {} as a function, when it is a {}.",
-                    callee.clone(),
-                    callee
+                    "Used cognatio outside of a daemonium that has a parent. Line {}",
+                    keyword.line
                 );
-                return Err(emsg);
+                return Err(RuntimeError::new("name", emsg, Some(keyword.line)));
             }
         };
-        let mut evaledArgs: Vec<Value> = Vec::new();
-        for arg in args {
-            evaledArgs.push(self.interpret_expr(arg)?);
+        let anima = Token::new(
+            TokenType::Self_,
+            String::from("anima"),
+            Literal::Empty,
+            keyword.line,
+        );
+        let instance = match self.environment.borrow().get(anima) {
+            Some(Value::Instance(i)) => i,
+            _ => {
+                let emsg = format!("Used cognatio outside of a method. Line {}", keyword.line);
+                return Err(RuntimeError::new("name", emsg, Some(keyword.line)));
+            }
+        };
+        match superclass.find_method(&method.lexeme) {
+            Some(m) => Ok(Value::UserFn(m.bind(instance))),
+            None => {
+                let emsg = format!(
+                    "Undefined method {} on cognatio ({}). Line {}",
+                    method.lexeme, superclass, method.line
+                );
+                Err(RuntimeError::new("name", emsg, Some(method.line)))
+            }
         }
-        match evaled {
-            Value::NativeFn(f) => match f.call(self, evaledArgs) {
-                Ok(retval) => {
-                    //dbg!(self.environment.clone());
-                    self.retval = retval.clone();
-                    match retval {
-                        Some(rv) => {
-                            return Ok(rv)
-                        },
-                        None => {
-                            return Ok(Value::Empty)
-                        },
-                    }
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            },
-            Value::UserFn(f) => match f.call(self, evaledArgs) {
-                Ok((mutated, retval)) => {
-                    self.environment = mutated;
-                    self.retval = retval.clone();
-                    match retval {
-                        Some(rv) => {
-                            return Ok(rv);
-                        }
-                        None => {
-                            return Ok(Value::Empty);
-                        }
-                    }
-                }
-                Err(failure) => {
-                    let emsg = failure.1;
-                    return Err(emsg);
-                }
-            },
+    }
+
+    fn interpret_set(
+        &mut self,
+        object: Expr,
+        name: Token,
+        value: Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        let val = self.interpret_expr(value)?;
+        Interpreter::set_property(object, &name, val)
+    }
+
+    fn set_property(object: Value, name: &Token, val: Value) -> Result<Value, RuntimeError> {
+        let instance = match object {
+            Value::Instance(instance) => instance,
+            other => {
+                let emsg = format!(
+                    "Tried to set field {} on {}, but only instances have fields. Line {}",
+                    name.lexeme, other, name.line
+                );
+                return Err(RuntimeError::new("type", emsg, Some(name.line)));
+            }
+        };
+        instance.borrow_mut().set(name, val.clone());
+        Ok(val)
+    }
+
+    /// A compound assignment to a field or an element. The object (and the
+    /// index) are only evaluated once, so xs[f()] += 1 only calls f once.
+    fn interpret_update(
+        &mut self,
+        target: Expr,
+        oper: Token,
+        value: Expr,
+    ) -> Result<Value, RuntimeError> {
+        match target {
+            Expr::Get(object, name) => {
+                let object = self.interpret_expr(*object)?;
+                let current = Interpreter::get_property(object.clone(), &name)?;
+                let val = self.interpret_expr(value)?;
+                let val = self.apply_binary(current, oper, val)?;
+                Interpreter::set_property(object, &name, val)
+            }
+            Expr::Index(object, bracket, index) => {
+                let object = self.interpret_expr(*object)?;
+                let index = self.interpret_expr(*index)?;
+                let current = Interpreter::index_value(&object, &bracket, &index)?;
+                let val = self.interpret_expr(value)?;
+                let val = self.apply_binary(current, oper, val)?;
+                Interpreter::set_index_value(object, &bracket, &index, val)
+            }
             _ => {
                 let emsg = format!(
-                    "{} is neither a function, nor a language construct, it is a {}",
-                    callee, evaled
+                    "Attempted to apply {}= to {}, which can't be assigned to. Line {}",
+                    oper.lexeme, target, oper.line
                 );
-                return Err(emsg);
+                Err(RuntimeError::new("type", emsg, Some(oper.line)))
             }
         }
     }
 
-    fn interpret_literal(&mut self, value: Literal) -> Result<Value, String> {
+    fn interpret_literal(&mut self, value: Literal) -> Result<Value, RuntimeError> {
         match value {
+            Literal::Integer(x) => Ok(Value::Integer(x)),
+            Literal::BigInt(x) => Ok(Value::BigInt(x)),
             Literal::Number(x) => Ok(Value::Number(x)),
             Literal::StrLit(x) => Ok(Value::String(x)),
             Literal::Bool(x) => Ok(Value::Bool(x)),
@@ -366,7 +861,7 @@ impl Interpreter {
         left: Expr,
         operator: Token,
         right: Expr,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, RuntimeError> {
         let left = self.interpret_expr(left)?;
 
         // If we can short-circuit, then do.
@@ -390,7 +885,21 @@ impl Interpreter {
         self.interpret_expr(right)
     }
 
-    fn interpret_unary(&mut self, operator: Token, right: Expr) -> Result<Value, String> {
+    /// Only the branch that was picked gets evaluated.
+    fn interpret_conditional(
+        &mut self,
+        cond: Expr,
+        then: Expr,
+        otherwise: Expr,
+    ) -> Result<Value, RuntimeError> {
+        if Interpreter::is_truthy(self.interpret_expr(cond)?) {
+            self.interpret_expr(then)
+        } else {
+            self.interpret_expr(otherwise)
+        }
+    }
+
+    fn interpret_unary(&mut self, operator: Token, right: Expr) -> Result<Value, RuntimeError> {
         // Evaluate the operand that we are applying the operator too.
         let evaledright = self.interpret_expr(right.clone())?;
 
@@ -398,17 +907,17 @@ impl Interpreter {
         // we need to apply.
         match operator.ttype {
             TokenType::Minus => {
-                match evaledright {
-                    Value::Number(x) => {
-                        return Ok(Value::Number(-x));
+                match numeric::negate(&evaledright) {
+                    Some(res) => {
+                        return Ok(res);
                     }
-                    _ => {
+                    None => {
                         // If we somehow got to the point where a unary oper
                         // is being applied to something other than a number we should
                         // probably let the user know and be scared.
                         //let emsg = format!("Attempted to interpret a unary operation with the invalid operator {:?}", operator);
-                        let emsg = format!("Attempted to apply unary operator '{}' to expression {}, which is invalid.", operator.lexeme, right);
-                        return Err(emsg);
+                        let emsg = format!("Attempted to apply unary operator '{}' to expression {}, which is invalid. Line {}", operator.lexeme, right, operator.line);
+                        return Err(RuntimeError::new("type", emsg, Some(operator.line)));
                     }
                 }
             }
@@ -437,28 +946,150 @@ impl Interpreter {
                     "Attempted to interpret unary operation with expr {:?}",
                     right.clone()
                 );
-                return Err(errormsg);
+                return Err(RuntimeError::new("runtime", errormsg, Some(operator.line)));
+            }
+        }
+    }
+
+    fn interpret_list(&mut self, items: Vec<Expr>) -> Result<Value, RuntimeError> {
+        let mut values: Vec<Value> = Vec::new();
+        for item in items {
+            values.push(self.interpret_expr(item)?);
+        }
+        Ok(Value::List(Rc::new(RefCell::new(values))))
+    }
+
+    /// Each piece of an interpolated string is joined together using it's
+    /// Display representation.
+    fn interpret_interpolate(&mut self, parts: Vec<Expr>) -> Result<Value, RuntimeError> {
+        let mut out = String::new();
+        for part in parts {
+            let val = self.interpret_expr(part)?;
+            out.push_str(&val.to_string());
+        }
+        Ok(Value::String(out))
+    }
+
+    fn interpret_map(
+        &mut self,
+        brace: Token,
+        pairs: Vec<(Expr, Expr)>,
+    ) -> Result<Value, RuntimeError> {
+        let mut map: FxHashMap<MapKey, Value> = FxHashMap::default();
+        for (key, value) in pairs {
+            let key = self.interpret_expr(key)?;
+            let key =
+                MapKey::from_value(&key).map_err(|e| RuntimeError::at("type", e, brace.line))?;
+            let value = self.interpret_expr(value)?;
+            map.insert(key, value);
+        }
+        Ok(Value::Map(Rc::new(RefCell::new(map))))
+    }
+
+    /// Indexing works for both lists, by position, and maps, by key.
+    fn interpret_index(
+        &mut self,
+        object: Expr,
+        bracket: Token,
+        index: Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        let index = self.interpret_expr(index)?;
+        Interpreter::index_value(&object, &bracket, &index)
+    }
+
+    fn index_value(object: &Value, bracket: &Token, index: &Value) -> Result<Value, RuntimeError> {
+        match object {
+            Value::List(list) => {
+                let list = list.borrow();
+                let i = Interpreter::to_index(index, list.len())
+                    .map_err(|e| RuntimeError::at("index", e, bracket.line))?;
+                Ok(list[i].clone())
+            }
+            Value::Map(map) => {
+                let key = MapKey::from_value(index)
+                    .map_err(|e| RuntimeError::at("type", e, bracket.line))?;
+                match map.borrow().get(&key) {
+                    Some(val) => Ok(val.clone()),
+                    None => {
+                        let emsg = format!("Key {} is not in the map. Line {}", key, bracket.line);
+                        Err(RuntimeError::new("index", emsg, Some(bracket.line)))
+                    }
+                }
+            }
+            other => {
+                let emsg = format!(
+                    "Tried to index into {}, which is neither a list nor a map. Line {}",
+                    other, bracket.line
+                );
+                Err(RuntimeError::new("type", emsg, Some(bracket.line)))
+            }
+        }
+    }
+
+    fn interpret_set_index(
+        &mut self,
+        object: Expr,
+        bracket: Token,
+        index: Expr,
+        value: Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        let index = self.interpret_expr(index)?;
+        let val = self.interpret_expr(value)?;
+        Interpreter::set_index_value(object, &bracket, &index, val)
+    }
+
+    fn set_index_value(
+        object: Value,
+        bracket: &Token,
+        index: &Value,
+        val: Value,
+    ) -> Result<Value, RuntimeError> {
+        match object {
+            Value::List(list) => {
+                let mut list = list.borrow_mut();
+                let i = Interpreter::to_index(index, list.len())
+                    .map_err(|e| RuntimeError::at("index", e, bracket.line))?;
+                list[i] = val.clone();
+            }
+            // Assigning to a key that isn't in the map yet adds it.
+            Value::Map(map) => {
+                let key = MapKey::from_value(index)
+                    .map_err(|e| RuntimeError::at("type", e, bracket.line))?;
+                map.borrow_mut().insert(key, val.clone());
+            }
+            other => {
+                let emsg = format!(
+                    "Tried to assign to an index of {}, which is neither a list nor a map. Line {}",
+                    other, bracket.line
+                );
+                return Err(RuntimeError::new("type", emsg, Some(bracket.line)));
             }
         }
+        Ok(val)
     }
 
-    fn interpret_var_expr(&mut self, name: Token) -> Result<Value, String> {
-        match self.environment.get(name.clone()) {
+    fn interpret_var_expr(&mut self, name: Token) -> Result<Value, RuntimeError> {
+        match self.environment.borrow().get(name.clone()) {
             Some(x) => {
                 return Ok(x);
             }
             None => {
-                dbg!(self.environment.clone());
                 let emsg = format!(
-                    "Tried to access undefined variable with the name {}",
-                    name.lexeme.clone()
+                    "Tried to access undefined variable with the name {}. Line {}",
+                    name.lexeme.clone(),
+                    name.line
                 );
-                return Err(emsg);
+                return Err(RuntimeError::new("name", emsg, Some(name.line)));
             }
         }
     }
 
     fn is_equal(&mut self, lv: Value, rv: Value) -> bool {
+        if let Some(eq) = numeric::equal(&lv, &rv) {
+            return eq;
+        }
         if lv == Value::Empty && rv == Value::Empty {
             return true;
         }
@@ -471,6 +1102,36 @@ impl Interpreter {
     // Assoc functions. We dont need to take self for these so, we'll avoid
     // the java-ism of making them methods.
 
+    fn file_name(path: &Path) -> String {
+        match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => path.display().to_string(),
+        }
+    }
+
+    /// Check that a value can be used to index into a list of the given length.
+    /// Indexes must be whole numbers, and inside the bounds of the list.
+    pub fn to_index(index: &Value, len: usize) -> Result<usize, String> {
+        let i = match index {
+            Value::Integer(x) => Some(*x),
+            Value::Number(x) => numeric::float_to_integer(*x),
+            _ => None,
+        };
+        match i.map(usize::try_from) {
+            Some(Ok(i)) => {
+                if i < len {
+                    Ok(i)
+                } else {
+                    Err(format!(
+                        "Index {} is out of bounds for a list of length {}",
+                        i, len
+                    ))
+                }
+            }
+            _ => Err(format!("{} is not a valid list index", index)),
+        }
+    }
+
     /// Truthyness is wheater a value is treated as true, or false.
     /// Booleans evaluate to themselves, Empty types are false, everything
     /// else is truth-y. This is shamelessly inspired by how our zen masters
@@ -505,8 +1166,10 @@ mod tests {
     use super::Value;
     use super::*;
     use crate::ast::ASTNode;
+    use crate::bigint::BigInt;
     use crate::environment::Environment;
     use crate::parser::Parser;
+    use crate::rational::Rational;
     use crate::scanner::*;
     use crate::token::*;
 
@@ -515,7 +1178,7 @@ mod tests {
         let test_str: String = "5 + 10;".to_string();
         let parsed = process(test_str);
         let result = extract_retval(parsed);
-        let expected = Value::Number(15.0);
+        let expected = Value::Integer(15);
         assert!(result == expected);
     }
 
@@ -524,7 +1187,7 @@ mod tests {
         let test_str: String = "10 - 5;".to_string();
         let parsed = process(test_str);
         let result = extract_retval(parsed);
-        let expected = Value::Number(5.0);
+        let expected = Value::Integer(5);
         assert!(result == expected);
     }
 
@@ -533,7 +1196,7 @@ mod tests {
         let test_str: String = "3 * 5;".to_string();
         let parsed = process(test_str);
         let result = extract_retval(parsed);
-        let expected = Value::Number(15.0);
+        let expected = Value::Integer(15);
         assert!(result == expected);
     }
 
@@ -542,10 +1205,167 @@ mod tests {
         let test_str: String = "100 / 10;".to_string();
         let parsed = process(test_str);
         let result = extract_retval(parsed);
-        let expected = Value::Number(10.0);
+        let expected = Value::Integer(10);
         assert!(result == expected);
     }
 
+    #[test]
+    fn division_by_zero() {
+        let test_str: String = "0 / 5;".to_string();
+        let result = extract_retval(process(test_str));
+        assert!(result == Value::Integer(0));
+        for test_str in ["5 / 0;", "5.0 / 0.0;", "5 \\ 0;", "5 % 0;"] {
+            let mut i: Interpreter = Interpreter::new();
+            assert!(i.interpret(process(test_str.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn modulo_and_integer_division() {
+        let cases = [
+            ("17 % 5;", Value::Integer(2)),
+            ("-7 % 3;", Value::Integer(-1)),
+            ("17 \\ 5;", Value::Integer(3)),
+            ("-7 \\ 2;", Value::Integer(-3)),
+            ("7.5 % 2;", Value::Number(1.5)),
+            ("-7.0 \\ 2;", Value::Number(-3.0)),
+        ];
+        for (test_str, expected) in cases {
+            let result = extract_retval(process(test_str.to_string()));
+            assert!(result == expected);
+        }
+    }
+
+    #[test]
+    fn exponentiation() {
+        let cases = [
+            ("2 ** 10;", Value::Integer(1024)),
+            ("2 ** 3 ** 2;", Value::Integer(512)),
+            ("-2 ** 2;", Value::Integer(-4)),
+            ("2 ** -1 == ratio(1, 2);", Value::Bool(true)),
+            ("2.0 ** -1;", Value::Number(0.5)),
+            ("3 * 2 ** 2;", Value::Integer(12)),
+            ("2.0 ** 2;", Value::Number(4.0)),
+        ];
+        for (test_str, expected) in cases {
+            let result = extract_retval(process(test_str.to_string()));
+            assert!(result == expected);
+        }
+    }
+
+    #[test]
+    fn integers() {
+        let cases = [
+            ("9007199254740993 + 0;", Value::Integer(9007199254740993)),
+            ("1 + 2.5;", Value::Number(3.5)),
+            ("2 * 3;", Value::Integer(6)),
+            ("1 == 1.0;", Value::Bool(true)),
+            ("3 > 2.5;", Value::Bool(true)),
+            ("-(2);", Value::Integer(-2)),
+        ];
+        for (test_str, expected) in cases {
+            let result = extract_retval(process(test_str.to_string()));
+            assert!(result == expected);
+        }
+    }
+
+    #[test]
+    fn big_integers() {
+        let big = |x: &str| Value::BigInt(Rc::new(BigInt::parse(x, 10).unwrap()));
+        let cases = [
+            ("9223372036854775807 + 1;", big("9223372036854775808")),
+            ("-9223372036854775807 - 2;", big("-9223372036854775809")),
+            ("4294967296 * 4294967296;", big("18446744073709551616")),
+            ("2 ** 100;", big("1267650600228229401496703205376")),
+            ("(2 ** 64) \\ (2 ** 60);", Value::Integer(16)),
+            ("(2 ** 64) - 1 - (2 ** 64);", Value::Integer(-1)),
+            ("-9223372036854775808;", Value::Integer(i64::MIN)),
+            ("2 ** 64 > 2 ** 63;", Value::Bool(true)),
+            ("2 ** 64 == 18446744073709551616.0;", Value::Bool(true)),
+            ("2 ** 64 + 1 == 18446744073709551616.0;", Value::Bool(false)),
+        ];
+        for (test_str, expected) in cases {
+            let result = extract_retval(process(test_str.to_string()));
+            assert!(result == expected);
+        }
+    }
+
+    #[test]
+    fn fibonacci_stays_exact() {
+        let test_str = "ligamen a = 0; ligamen b = 1;
+            enim (ligamen i = 0; i < 100; i = i + 1) { ligamen t = a + b; a = b; b = t; }";
+        let symbol = Token::new(TokenType::Identifier, "a".to_string(), Literal::Empty, 1);
+        let expected = BigInt::parse("354224848179261915075", 10).unwrap();
+        let parsed = process(test_str.to_string());
+        assert!(eval_and_expect(
+            parsed,
+            Some(Value::BigInt(Rc::new(expected))),
+            symbol
+        ));
+    }
+
+    #[test]
+    fn rationals() {
+        let ratio = |n: i64, d: i64| {
+            Value::Rational(Rc::new(
+                Rational::new(BigInt::from_i64(n), BigInt::from_i64(d)).unwrap(),
+            ))
+        };
+        let cases = [
+            ("ratio(2, 4);", ratio(1, 2)),
+            ("ratio(1, 3) + ratio(1, 6);", ratio(1, 2)),
+            ("ratio(1, 3) * 3;", Value::Integer(1)),
+            ("ratio(1, 2) / 3;", ratio(1, 6)),
+            ("1 / 3;", ratio(1, 3)),
+            ("1 / 3 * 3 == 1;", Value::Bool(true)),
+            ("1 / 3 * 3;", Value::Integer(1)),
+            ("-6 / 4;", ratio(-3, 2)),
+            ("10 / 4.0;", Value::Number(2.5)),
+            ("(2 ** 100) / (2 ** 98);", Value::Integer(4)),
+            (
+                "(2 ** 100 + 1) / (2 ** 100) * (2 ** 100) == 2 ** 100 + 1;",
+                Value::Bool(true),
+            ),
+            ("(2 ** 100 + 1) / (2 ** 100) > 1;", Value::Bool(true)),
+            ("ratio(7, 2) \\ 1;", Value::Integer(3)),
+            ("ratio(7, 2) % 1;", ratio(1, 2)),
+            ("ratio(2, 3) ** -2;", ratio(9, 4)),
+            ("2 ** -2;", ratio(1, 4)),
+            ("1 ** -5;", Value::Integer(1)),
+            ("(2 ** 100) ** -1 * (2 ** 100) == 1;", Value::Bool(true)),
+            ("-ratio(1, 2);", ratio(-1, 2)),
+            ("ratio(1, 2) + 0.25;", Value::Number(0.75)),
+            ("ratio(1, 2) == 0.5;", Value::Bool(true)),
+            ("ratio(1, 3) < 0.3333334;", Value::Bool(true)),
+            ("ratio(1, 3) > 0;", Value::Bool(true)),
+            ("mutare(\"3/6\");", ratio(1, 2)),
+            (
+                "mutare(\"18446744073709551616\") == 2 ** 64;",
+                Value::Bool(true),
+            ),
+            ("mutare(ratio(-3, 4));", Value::String("-3/4".to_string())),
+        ];
+        for (test_str, expected) in cases {
+            let result = extract_retval(process(test_str.to_string()));
+            assert!(result == expected);
+        }
+        for test_str in [
+            "ratio(1, 0);",
+            "ratio(1.5, 2);",
+            "ratio(1, 2) / 0;",
+            "0 ** -1;",
+            "(2 ** 64) ** -(2 ** 40);",
+        ] {
+            let mut i: Interpreter = Interpreter::new();
+            assert!(i.interpret(process(test_str.to_string())).is_err());
+        }
+        let mut i: Interpreter = Interpreter::new();
+        let err = i
+            .interpret(process(String::from("ratio(1, 0);")))
+            .unwrap_err();
+        assert!(err.starts_with("Attempted to make a ratio with a denominator of zero! Got 0"));
+    }
+
     #[test]
     fn string_concat() {
         let test_str: String = "\"Hello, \" + \"World!\";".to_string();
@@ -624,8 +1444,8 @@ mod tests {
             Literal::Empty,
             3,
         );
-        let expected = Value::Number(1337.0);
-        assert!(env.get(symbol).unwrap() == expected);
+        let expected = Value::Integer(1337);
+        assert!(env.borrow().get(symbol).unwrap() == expected);
     }
 
     #[test]
@@ -644,8 +1464,8 @@ mod tests {
             Literal::Empty,
             3,
         );
-        let expected = Value::Number(1337.0);
-        assert!(env.get(symbol).unwrap() == expected);
+        let expected = Value::Integer(1337);
+        assert!(env.borrow().get(symbol).unwrap() == expected);
     }
 
     #[test]
@@ -665,8 +1485,8 @@ mod tests {
             Literal::Empty,
             3,
         );
-        let expected = Value::Number(1337.0);
-        assert!(env.get(symbol).unwrap() == expected);
+        let expected = Value::Integer(1337);
+        assert!(env.borrow().get(symbol).unwrap() == expected);
     }
 
     #[test]
@@ -685,8 +1505,8 @@ mod tests {
             Literal::Empty,
             3,
         );
-        let expected = Value::Number(1337.0);
-        assert!(env.get(symbol).unwrap() == expected);
+        let expected = Value::Integer(1337);
+        assert!(env.borrow().get(symbol).unwrap() == expected);
     }
 
     #[test]
@@ -704,13 +1524,14 @@ mod tests {
             Literal::Empty,
             1,
         );
-        let result = env.get(symbol.clone());
+        let result = env.borrow().get(symbol.clone());
         let expected = Some(Value::UserFn(UserFunction::new(
             symbol,
             vec![Stmt::Expression(Expr::Literal(Literal::StrLit(
                 String::from("Hi"),
             )))],
             vec![],
+            env.clone(),
         )));
         assert!(result == expected);
     }
@@ -797,7 +1618,7 @@ mod tests {
             Literal::Empty,
             1,
         );
-        let expected = eval_and_expect(parsed, Some(Value::Number(100.0)), symbol);
+        let expected = eval_and_expect(parsed, Some(Value::Integer(100)), symbol);
         assert!(expected == true);
     }
 
@@ -813,7 +1634,7 @@ mod tests {
             Literal::Empty,
             1,
         );
-        let expected = eval_and_expect(parsed, Some(Value::Number(1337.0)), symbol);
+        let expected = eval_and_expect(parsed, Some(Value::Integer(1337)), symbol);
         assert!(expected == true);
     }
 
@@ -830,7 +1651,7 @@ mod tests {
             Literal::Empty,
             1,
         );
-        let expected = eval_and_expect(parsed, Some(Value::Number(1337.0)), symbol);
+        let expected = eval_and_expect(parsed, Some(Value::Integer(1337)), symbol);
         assert!(expected == true);
     }
 
@@ -846,7 +1667,949 @@ mod tests {
             Literal::Empty,
             1,
         );
-        let expected = eval_and_expect(parsed, Some(Value::Number(2.0)), symbol);
+        let expected = eval_and_expect(parsed, Some(Value::Integer(2)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn class_fields() {
+        let test = String::from(
+            r#"daemonium Familiar {}
+            ligamen f = Familiar();
+            f.nomen = "Imp";
+            ligamen testVal = f.nomen;"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::String(String::from("Imp"))), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn class_methods() {
+        let test = String::from(
+            r#"daemonium Familiar {
+              incantatio initium(nomen) {
+                anima.nomen = nomen;
+              }
+              incantatio salve() {
+                beneficium "Ave, " + anima.nomen;
+              }
+            }
+            ligamen f = Familiar("Imp");
+            ligamen testVal = f.salve();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(
+            parsed,
+            Some(Value::String(String::from("Ave, Imp"))),
+            symbol,
+        );
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn instances_are_shared() {
+        let test = String::from(
+            r#"daemonium Familiar {}
+            ligamen a = Familiar();
+            ligamen b = a;
+            b.vis = 5;
+            ligamen testVal = a.vis;"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(5)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn inherited_methods() {
+        let test = String::from(
+            r#"daemonium Familiar {
+              incantatio salve() {
+                beneficium "Ave";
+              }
+            }
+            daemonium Imp < Familiar {}
+            ligamen testVal = Imp().salve();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::String(String::from("Ave"))), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn super_calls() {
+        let test = String::from(
+            r#"daemonium Familiar {
+              incantatio salve() {
+                beneficium "Ave, " + anima.nomen;
+              }
+            }
+            daemonium Imp < Familiar {
+              incantatio initium() {
+                anima.nomen = "Imp";
+              }
+              incantatio salve() {
+                beneficium cognatio.salve() + "!";
+              }
+            }
+            ligamen testVal = Imp().salve();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(
+            parsed,
+            Some(Value::String(String::from("Ave, Imp!"))),
+            symbol,
+        );
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn inherit_from_non_class() {
+        let test = String::from("ligamen Familiar = 5; daemonium Imp < Familiar {}");
+        let parsed = process(test);
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i.interpret(parsed).is_err());
+    }
+
+    #[test]
+    fn closures() {
+        let test = String::from(
+            r#"incantatio facere() {
+              ligamen c = 0;
+              incantatio augere() {
+                c = c + 1;
+                beneficium c;
+              }
+              beneficium augere;
+            }
+            ligamen numerator = facere();
+            numerator();
+            ligamen testVal = numerator();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(2)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn lexical_scope() {
+        let test = String::from(
+            r#"ligamen x = "global";
+            incantatio show() {
+              beneficium x;
+            }
+            incantatio f() {
+              ligamen x = "local";
+              beneficium show();
+            }
+            ligamen testVal = f();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::String(String::from("global"))), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn params_dont_clobber_caller() {
+        let test = String::from(
+            r#"ligamen testVal = 1337;
+            incantatio f(testVal) {
+              ligamen local = testVal;
+            }
+            f(1312);"#,
+        );
+        let parsed = process(test);
+        let env = eval_and_extract_state(parsed);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        assert!(env.borrow().get(symbol).unwrap() == Value::Integer(1337));
+        let local = Token::new(
+            TokenType::Identifier,
+            String::from("local"),
+            Literal::Empty,
+            3,
+        );
+        assert!(env.borrow().get(local).is_none());
+    }
+
+    #[test]
+    fn block_scope() {
+        let test = String::from("ligamen testVal = 0; { ligamen inner = 1; testVal = inner; }");
+        let parsed = process(test);
+        let env = eval_and_extract_state(parsed);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        assert!(env.borrow().get(symbol).unwrap() == Value::Integer(1));
+        let inner = Token::new(
+            TokenType::Identifier,
+            String::from("inner"),
+            Literal::Empty,
+            1,
+        );
+        assert!(env.borrow().get(inner).is_none());
+    }
+
+    #[test]
+    fn early_return() {
+        let test = String::from(
+            r#"incantatio signum(x) {
+              si(x < 0) {
+                beneficium "negative";
+              }
+              beneficium "positive";
+            }
+            ligamen testVal = signum(-5);"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(
+            parsed,
+            Some(Value::String(String::from("negative"))),
+            symbol,
+        );
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn return_from_loop() {
+        let test = String::from(
+            r#"incantatio quaerere() {
+              enim(ligamen i = 0; i < 100; i = i + 1) {
+                si(i == 7) {
+                  beneficium i;
+                }
+              }
+              beneficium -1;
+            }
+            ligamen testVal = quaerere();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(7)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn return_values_dont_leak() {
+        let test = String::from(
+            r#"incantatio quinque() {
+              beneficium 5;
+            }
+            incantatio nihilum() {}
+            quinque();
+            ligamen testVal = nihilum();"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Empty), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn break_test() {
+        let test = String::from(
+            r#"ligamen testVal = 0;
+            dum(verum) {
+              si(testVal == 10) {
+                frange;
+              }
+              testVal = testVal + 1;
+            }"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(10)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn continue_runs_increment() {
+        let test = String::from(
+            r#"ligamen testVal = 0;
+            enim(ligamen i = 0; i < 10; i = i + 1) {
+              si(i < 5) {
+                perge;
+              }
+              testVal = testVal + 1;
+            }"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(5)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn list_indexing() {
+        let test =
+            String::from("ligamen xs = [1, 2, 3]; xs[0] = 10; ligamen testVal = xs[0] + xs[2];");
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(13)), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn list_natives() {
+        let test = String::from(
+            r#"ligamen xs = [];
+            addere(xs, 1);
+            addere(xs, 2);
+            addere(xs, 3);
+            demere(xs);
+            ligamen testVal = [longitudo(xs), secare(xs, 1, 2)];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let inner = Value::List(Rc::new(RefCell::new(vec![Value::Integer(2)])));
+        let expected = Value::List(Rc::new(RefCell::new(vec![Value::Integer(2), inner])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn list_out_of_bounds() {
+        let test = String::from("ligamen xs = [1, 2, 3]; xs[3];");
+        let parsed = process(test);
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i.interpret(parsed).is_err());
+    }
+
+    #[test]
+    fn map_lookup_and_assign() {
+        let test = String::from(
+            r#"ligamen m = { "a": 1, 2: "b" };
+            m["a"] = m["a"] + 1;
+            m[verum] = 3;
+            ligamen testVal = [m["a"], m[2.0], m[verum], longitudo(m), habet(m, "c")];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            Value::Integer(2),
+            Value::String(String::from("b")),
+            Value::Integer(3),
+            Value::Integer(3),
+            Value::Bool(false),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn map_keys() {
+        let test = String::from(
+            r#"ligamen m = { "b": 1, "a": 2, 0: 3 };
+            ligamen testVal = claves(m);"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            Value::Integer(0),
+            Value::String(String::from("a")),
+            Value::String(String::from("b")),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn map_missing_and_unhashable_keys() {
+        let test = String::from(r#"ligamen m = { "a": 1 }; m["b"];"#);
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i.interpret(process(test)).is_err());
+        let test = String::from(r#"ligamen m = { [1]: 1 };"#);
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i.interpret(process(test)).is_err());
+    }
+
+    #[test]
+    fn call_any_expression() {
+        let test = String::from(
+            r#"incantatio make() {
+                incantatio inner(x) { beneficium x * 2; }
+                beneficium inner;
+            }
+            ligamen fs = [make()];
+            ligamen testVal = [make()(2), (make())(3), fs[0](4)];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            Value::Integer(4),
+            Value::Integer(6),
+            Value::Integer(8),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn call_non_callable() {
+        let test = String::from("ligamen xs = [1]; xs[0]();");
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i.interpret(process(test)).is_err());
+    }
+
+    #[test]
+    fn lambdas() {
+        let test = String::from(
+            r#"incantatio apply(f, x) { beneficium f(x); }
+            ligamen n = 10;
+            ligamen add = incantatio (x) { beneficium x + n; };
+            ligamen testVal = [apply(add, 1), apply(incantatio (x) { beneficium x * 2; }, 3)];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            Value::Integer(11),
+            Value::Integer(6),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn arity_is_checked() {
+        let cases = [
+            "incantatio f(a, b) { beneficium a; } f(1);",
+            "incantatio f(a) { beneficium a; } f(1, 2);",
+            "longitudo();",
+            "daemonium D { incantatio initium(x) { anima.x = x; } } D();",
+            "daemonium D {} D(1);",
+        ];
+        for test in cases {
+            let mut i: Interpreter = Interpreter::new();
+            assert!(i.interpret(process(test.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn arity_error_message() {
+        let test = String::from("incantatio f(a, b) { beneficium a; }\nf(1);");
+        let mut i: Interpreter = Interpreter::new();
+        let err = i.interpret(process(test)).unwrap_err();
+        assert!(err == "f expects 2 arguments but was called with 1. Line 2");
+    }
+
+    #[test]
+    fn default_params() {
+        let test = String::from(
+            r#"ligamen calls = 0;
+            incantatio count() { calls = calls + 1; beneficium calls; }
+            incantatio f(a, b = a * 2, c = count()) { beneficium [a, b, c]; }
+            ligamen testVal = [f(1), f(1, 5), f(1, 5, 0), f(1)];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let list = |xs: Vec<i64>| {
+            Value::List(Rc::new(RefCell::new(
+                xs.into_iter().map(Value::Integer).collect(),
+            )))
+        };
+        // Defaults are evaluated afresh on every call that needs them.
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            list(vec![1, 2, 1]),
+            list(vec![1, 5, 2]),
+            list(vec![1, 5, 0]),
+            list(vec![1, 2, 3]),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn manere_negative() {
+        // Waiting for a negative time doesn't wait at all.
+        let mut i: Interpreter = Interpreter::new();
+        assert!(i
+            .interpret(process(String::from("manere(-1); manere(-2.5);")))
+            .is_ok());
+    }
+
+    #[test]
+    fn printing_defaults() {
+        let test = String::from(
+            r#"daemonium A { incantatio m(k = (1 * 2), n = -k, o = !verum et f(k, p: 1)) {} }
+            ligamen g = incantatio (x = -1, y = x vel 2, ...z) {};
+            "{A().m} {g}";"#,
+        );
+        let mut i: Interpreter = Interpreter::new();
+        let result = i.interpret(process(test)).unwrap();
+        let expected = r#"m :: (k = ("1" * "2"), n = -k, o = !"true" et f(k, p: "1")) incantatio :: (x = -"1", y = x vel "2", ...z)"#;
+        assert!(result.last() == Some(&Value::String(expected.to_string())));
+    }
+
+    #[test]
+    fn named_and_rest_args() {
+        let test = String::from(
+            r#"incantatio f(a, b = 2, c = 3) { beneficium [a, b, c]; }
+            incantatio g(a, ...rest) { beneficium rest; }
+            ligamen testVal = [f(c: 9, a: 1), g(1), g(1, 2, 3)];"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let list = |xs: Vec<i64>| {
+            Value::List(Rc::new(RefCell::new(
+                xs.into_iter().map(Value::Integer).collect(),
+            )))
+        };
+        let expected = Value::List(Rc::new(RefCell::new(vec![
+            list(vec![1, 2, 9]),
+            list(vec![]),
+            list(vec![2, 3]),
+        ])));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn bad_named_args() {
+        let cases = [
+            "incantatio f(a, b = 2) { beneficium a; } f(b: 1);",
+            "incantatio f(a, b = 2) { beneficium a; } f(1, a: 1);",
+            "incantatio f(a, b = 2) { beneficium a; } f(1, c: 1);",
+            "incantatio f(a, ...r) { beneficium a; } f(1, r: 1);",
+            "longitudo(x: [1]);",
+        ];
+        for test in cases {
+            let mut i: Interpreter = Interpreter::new();
+            assert!(i.interpret(process(test.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn string_interpolation() {
+        let test = String::from(
+            r#"ligamen nomen = "Caesar";
+            ligamen xs = [1, 2];
+            ligamen testVal = "Ave, {nomen}! {1 + 2} {xs} {longitudo("{nomen}")}";"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::String(String::from("Ave, Caesar! 3 [1, 2] 6"));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn roman_numerals() {
+        let test_str = String::from("romanus(#MCMXCIV + 30);");
+        let result = extract_retval(process(test_str));
+        assert!(result == Value::String(String::from("MMXXIV")));
+        for test_str in ["romanus(0);", "romanus(4000);", "romanus(1.5);"] {
+            let mut i: Interpreter = Interpreter::new();
+            assert!(i.interpret(process(test_str.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn catching_errors() {
+        let test = String::from(
+            r#"incantatio fail(x) {
+              iacere "bad " + x;
+            }
+            ligamen testVal = "";
+            conare {
+              fail("omen");
+              testVal = "unreachable";
+            } capere (err) {
+              testVal = "{err["kind"]} {err["message"]} {err["line"]}";
+            }
+            conare {
+              ligamen x = 1 / 0;
+            } capere (err) {
+              testVal = testVal + ", {err["kind"]} {err["line"]}";
+            }
+            conare {
+              legere("/no/such/file.rit");
+            } capere (err) {
+              testVal = testVal + ", {err["kind"]} {err["line"]}";
+            }
+            conare {
+              conare {
+                iacere {"message": "inner", "kind": "custom"};
+              } capere (err) {
+                iacere err;
+              }
+            } capere (err) {
+              testVal = testVal + ", {err["kind"]} {err["message"]} {err["line"]}";
+            }
+            conare {
+              scribo nusquam;
+            } capere (err) {
+              testVal = testVal + ", {err["kind"]} {err["line"]}";
+            }"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = Value::String(String::from(
+            "error bad omen 2, arithmetic 12, io 17, custom inner 23, name 31",
+        ));
+        let expected = eval_and_expect(parsed, Some(expected), symbol);
+        assert!(expected == true);
+    }
+
+    #[test]
+    fn uncaught_errors() {
+        let test = String::from(
+            r#"ligamen testVal = 0;
+            dum(verum) {
+              conare {
+                frange;
+              } capere (err) {
+                testVal = 1;
+              }
+            }"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(0)), symbol);
+        assert!(expected == true);
+
+        let mut i: Interpreter = Interpreter::new();
+        let result = i.interpret(process(String::from("iacere \"omen\";")));
+        assert!(result == Err(String::from("error: omen. Line 1")));
+        // A later error shouldn't pick up the one that escaped.
+        let test = "ligamen kind = nihil; conare { 1 / 0; } capere (err) { kind = err[\"kind\"]; }";
+        i.interpret(process(String::from(test))).unwrap();
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("kind"),
+            Literal::Empty,
+            1,
+        );
+        let kind = i.environment.borrow().get(symbol);
+        assert!(kind == Some(Value::String(String::from("arithmetic"))));
+        assert!(i.interpret(process(String::from("iacere {};"))).is_err());
+    }
+
+    #[test]
+    fn error_kinds() {
+        let cases = [
+            ("1 + \"x\";", "type"),
+            ("1 / 0;", "arithmetic"),
+            ("2 ** (2 ** 40);", "arithmetic"),
+            ("nusquam;", "name"),
+            ("demere([]);", "index"),
+            ("secare([1, 2], 0, 5);", "index"),
+            ("habet({}, [1]);", "type"),
+            ("longitudo(1);", "type"),
+            ("ratio(1, 0);", "arithmetic"),
+        ];
+        for (test_str, kind) in cases {
+            let test = format!(
+                "ligamen kind = nihil; conare {{ {} }} capere (err) {{ kind = err[\"kind\"]; }}",
+                test_str
+            );
+            let mut i: Interpreter = Interpreter::new();
+            i.interpret(process(test)).unwrap();
+            let symbol = Token::new(
+                TokenType::Identifier,
+                String::from("kind"),
+                Literal::Empty,
+                1,
+            );
+            let found = i.environment.borrow().get(symbol);
+            assert!(
+                found == Some(Value::String(kind.to_string())),
+                "{}",
+                test_str
+            );
+        }
+    }
+
+    #[test]
+    fn modules() {
+        let dir = std::env::temp_dir().join(format!("daemonica_modules_{}", std::process::id()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        let files = [
+            (
+                "lib/astra.rit",
+                "invocabo \"stella.rit\"; ligamen n = 0; incantatio bump() { n = n + 1; beneficium n * stella.scale; }",
+            ),
+            ("lib/stella.rit", "ligamen scale = 10;"),
+            ("lib/ovum.rit", "invocabo \"gallina.rit\";"),
+            ("lib/gallina.rit", "invocabo \"ovum.rit\";"),
+        ];
+        for (name, src) in files {
+            fs::write(dir.join(name), src).unwrap();
+        }
+        let main = dir.join("main.rit");
+        let mut i = Interpreter::with_file(main.to_str().unwrap());
+        let test = String::from(
+            r#"invocabo "lib/astra.rit";
+            ligamen first = astra.bump();
+            invocabo "lib/astra.rit";
+            ligamen second = astra.bump();"#,
+        );
+        i.interpret(process(test)).unwrap();
+        let get = |name: &str| {
+            let symbol = Token::new(TokenType::Identifier, name.to_string(), Literal::Empty, 1);
+            i.environment.borrow().get(symbol)
+        };
+        assert!(get("first") == Some(Value::Integer(10)));
+        assert!(get("second") == Some(Value::Integer(20)));
+        // Nothing the module defined leaks out of it.
+        assert!(get("bump").is_none());
+        assert!(get("stella").is_none());
+
+        let mut i = Interpreter::with_file(main.to_str().unwrap());
+        for test_str in [
+            "invocabo \"lib/ovum.rit\";",
+            "invocabo \"lib/missing.rit\";",
+            "invocabo \"lib/astra.rit\"; astra.missing;",
+        ] {
+            assert!(i.interpret(process(test_str.to_string())).is_err());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn constants() {
+        let test = String::from(
+            r#"constans limit = 3;
+            ligamen testVal = 0;
+            {
+              constans limit = 10;
+              ligamen longitudo = limit;
+              testVal = longitudo;
+            }
+            incantatio f(horologium) { beneficium horologium + limit; }
+            testVal = testVal + f(1);"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::Integer(14)), symbol);
+        assert!(expected == true);
+
+        // These get past the parser, since it can't see the declarations.
+        let mut i: Interpreter = Interpreter::new();
+        i.interpret(process(String::from("constans x = 1;")))
+            .unwrap();
+        for test_str in [
+            "x = 2;",
+            "constans x = 2;",
+            "ligamen x = 2;",
+            "horologium = 1;",
+            "ligamen longitudo = 1;",
+            "incantatio manere() {}",
+        ] {
+            assert!(i.interpret(process(test_str.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let test = String::from(
+            r#"ligamen calls = 0;
+            ligamen xs = [1, 2, 3];
+            incantatio which() { calls += 1; beneficium xs; }
+            which()[calls] *= 10;
+            daemonium Punctum { incantatio initium() { anima.x = 5; } }
+            ligamen p = Punctum();
+            p.x -= 2;
+            p.x %= 2;
+            ligamen testVal = "a";
+            testVal += "b";
+            testVal += "{calls}{xs[1]}{p.x}";"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(parsed, Some(Value::String(String::from("ab1201"))), symbol);
+        assert!(expected == true);
+
+        let mut i: Interpreter = Interpreter::new();
+        i.interpret(process(String::from("ligamen m = {\"a\": 1};")))
+            .unwrap();
+        assert!(i
+            .interpret(process(String::from("m[\"b\"] += 1;")))
+            .is_err());
+        assert!(i
+            .interpret(process(String::from("m[\"a\"] /= 0;")))
+            .is_err());
+
+        // Each operator, on a plain variable.
+        let cases = [
+            ("ligamen n = 7; n += 2; n;", Value::Integer(9)),
+            ("ligamen n = 7; n -= 2; n;", Value::Integer(5)),
+            ("ligamen n = 7; n *= 2; n;", Value::Integer(14)),
+            ("ligamen n = 7; n /= 2; n == 3.5;", Value::Bool(true)),
+            ("ligamen n = 7; n %= 2; n;", Value::Integer(1)),
+            (
+                "ligamen n = 7; enim (ligamen i = 0; i < 3; i += 1) { n -= i; } n;",
+                Value::Integer(4),
+            ),
+        ];
+        for (test_str, expected) in cases {
+            let mut i: Interpreter = Interpreter::new();
+            let result = i.interpret(process(test_str.to_string())).unwrap();
+            assert!(result.last() == Some(&expected), "{}", test_str);
+        }
+    }
+
+    #[test]
+    fn conditional() {
+        let test = String::from(
+            r#"ligamen calls = 0;
+            incantatio f(x) { calls += 1; beneficium x; }
+            ligamen a = 1 < 2 ? f("minor") : f("maior");
+            ligamen b = nihil ? f(1) : 0 ? f(2) : f(3);
+            ligamen testVal = "{a} {b} {calls}";"#,
+        );
+        let parsed = process(test);
+        let symbol = Token::new(
+            TokenType::Identifier,
+            String::from("testVal"),
+            Literal::Empty,
+            1,
+        );
+        let expected = eval_and_expect(
+            parsed,
+            Some(Value::String(String::from("minor 2 2"))),
+            symbol,
+        );
         assert!(expected == true);
     }
 
@@ -860,7 +2623,7 @@ mod tests {
         i.interpret(ast).unwrap().get(0).unwrap().clone()
     }
 
-    fn eval_and_extract_state(ast: Vec<ASTNode>) -> Environment {
+    fn eval_and_extract_state(ast: Vec<ASTNode>) -> Rc<RefCell<Environment>> {
         let mut i: Interpreter = Interpreter::new();
         let x = i.interpret(ast);
         dbg!(i.environment.clone());
@@ -870,6 +2633,7 @@ mod tests {
 
     fn eval_and_expect(ast: Vec<ASTNode>, expected: Option<Value>, symb: Token) -> bool {
         let state = eval_and_extract_state(ast);
-        state.get(symb) == expected
+        let result = state.borrow().get(symb);
+        result == expected
     }
 }
@@ -1,6 +1,9 @@
 //! AST Level representation of Literal Values
 use core::fmt;
 use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
 
 /// Represent Literal Values that we parse.
 ///
@@ -14,6 +17,9 @@ use std::fmt::Display;
 /// As a wise man once said, there is power in a union.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Literal {
+    Integer(i64),
+    /// An integer literal too big to fit in an i64.
+    BigInt(Rc<BigInt>),
     Number(f64),
     StrLit(String),
     Bool(bool),
@@ -23,6 +29,12 @@ pub enum Literal {
 impl Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Literal::Integer(x) => {
+                write!(f, "{}", x)
+            }
+            Literal::BigInt(x) => {
+                write!(f, "{}", x)
+            }
             Literal::Number(x) => {
                 write!(f, "{}", x)
             }
@@ -1,10 +1,17 @@
 mod ast;
+mod bigint;
 mod callable;
+mod class;
 mod environment;
 mod interpreter;
 mod literals;
+mod mapkey;
+mod module;
 mod nativefn;
+mod numeric;
 mod parser;
+mod rational;
+mod roman;
 mod scanner;
 mod token;
 mod tokentype;
@@ -43,7 +50,7 @@ fn read_file(path: String) -> Result<String, io::Error> {
 }
 
 fn run_file(path: String) {
-    let mut interpreter = Interpreter::new();
+    let mut interpreter = Interpreter::with_file(&path);
     let ritual = read_file(path);
     run(ritual.unwrap(), &mut interpreter);
 }
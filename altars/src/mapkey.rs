@@ -0,0 +1,99 @@
+//! Keys for Daemonica maps.
+use core::fmt;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::ast::Value;
+use crate::bigint::BigInt;
+use crate::numeric;
+use crate::rational::Rational;
+
+/// Only some values can be used to key a map, strings, numbers and booleans.
+///
+/// Two keys are the same exactly when [crate::interpreter::Interpreter::is_equal]
+/// says that the values they came from are equal. Since 1 == 1.0 and
+/// 0.5 == ratio(1, 2), finite floats are stored as the exact number they are
+/// equal to. That leaves the infinities, which are stored by their bits so
+/// that they can be hashed. We have to refuse NaN, which isn't even equal to
+/// itself.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Bool(bool),
+    Integer(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Number(u64),
+    String(String),
+}
+
+impl MapKey {
+    pub fn from_value(val: &Value) -> Result<MapKey, String> {
+        match val {
+            Value::Bool(x) => Ok(MapKey::Bool(*x)),
+            Value::Integer(x) => Ok(MapKey::Integer(*x)),
+            Value::BigInt(x) => Ok(MapKey::BigInt((**x).clone())),
+            Value::Rational(x) => Ok(MapKey::Rational((**x).clone())),
+            Value::Number(x) if x.is_nan() => {
+                Err(String::from("NaN can not be used as the key of a map"))
+            }
+            // This also takes care of -0, which is equal to 0.
+            Value::Number(x) => match numeric::float_to_exact(*x) {
+                Some(exact) => MapKey::from_value(&exact),
+                None => Ok(MapKey::Number(x.to_bits())),
+            },
+            Value::String(x) => Ok(MapKey::String(x.clone())),
+            _ => Err(format!(
+                "{} can not be used as the key of a map. Only strings, numbers and booleans can",
+                val
+            )),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            MapKey::Bool(x) => Value::Bool(*x),
+            MapKey::Integer(x) => Value::Integer(*x),
+            MapKey::BigInt(x) => Value::BigInt(Rc::new(x.clone())),
+            MapKey::Rational(x) => Value::Rational(Rc::new(x.clone())),
+            MapKey::Number(x) => Value::Number(f64::from_bits(*x)),
+            MapKey::String(x) => Value::String(x.clone()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            MapKey::Bool(_) => 0,
+            MapKey::String(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Keys are ordered so that we can list them predictably. Booleans come first,
+/// then numbers, then strings.
+impl Ord for MapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (MapKey::Bool(x), MapKey::Bool(y)) => x.cmp(y),
+            (MapKey::String(x), MapKey::String(y)) => x.cmp(y),
+            // Both numbers, and none of them NaN, so they always have an order.
+            _ if self.rank() == 1 && other.rank() == 1 => {
+                numeric::order(&self.to_value(), &other.to_value()).unwrap()
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for MapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
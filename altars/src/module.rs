@@ -0,0 +1,65 @@
+//! Rituals that have been loaded from another file with invocabo.
+use core::fmt;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::{
+    ast::Value,
+    environment::{Binding, Environment},
+    interpreter::RuntimeError,
+    token::Token,
+};
+
+/// Each module is run in it's own global scope, so that nothing it defines
+/// leaks into the ritual that loaded it. Those definitions are then reached
+/// through the module, eg. astra.f().
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    /// Where the module was loaded from, which is also what it is cached by.
+    pub path: PathBuf,
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl Module {
+    pub fn new(name: String, path: PathBuf, environment: Rc<RefCell<Environment>>) -> Module {
+        Module {
+            name,
+            path,
+            environment,
+        }
+    }
+
+    /// Anything in the module's global scope can be reached, which includes
+    /// the builtins, since they live there too.
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        match self.environment.borrow().values.get(&name.lexeme) {
+            Some(Binding {
+                value: Some(val), ..
+            }) => Ok(val.clone()),
+            _ => {
+                let emsg = format!(
+                    "Module {} has no definition named {}. Line {}",
+                    self.name, name.lexeme, name.line
+                );
+                Err(RuntimeError::new("name", emsg, Some(name.line)))
+            }
+        }
+    }
+}
+
+/// A file is only ever loaded once, so two modules are the same if they came
+/// from the same place.
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Module({})", self.name)
+    }
+}
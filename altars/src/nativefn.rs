@@ -1,21 +1,23 @@
 //! Language-level functions and builtins.
 use core::{fmt, time};
 use std::{
+    cell::RefCell,
     collections::HashMap,
+    rc::Rc,
     thread,
     time::{SystemTime, UNIX_EPOCH}, io::{self, BufRead}, fs,
 };
 
 use fxhash::hash64;
 
-use crate::{ast::Value, callable::Callable, interpreter::Interpreter};
+use crate::{ast::Value, bigint::BigInt, callable::{Arity, Callable}, interpreter::{Interpreter, RuntimeError}, mapkey::MapKey, numeric, rational::Rational, roman};
 
 #[derive(Clone)]
 pub struct NativeFn {
     name: String,
     arity: usize,
     // We can implement a callable as a closure type here.
-    func: fn(&mut Interpreter, Vec<Value>) -> Result<Value, String>,
+    func: fn(&mut Interpreter, Vec<Value>) -> Result<Value, RuntimeError>,
 }
 
 impl PartialEq for NativeFn {
@@ -32,19 +34,21 @@ impl NativeFn {
         format!("{}.{}", self.name, self.arity)
     }
 
-    pub fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Option<Value>, String> {
-        let res = (self.func)(interpreter, args)?;
-        //interpreter.retval = Some(res);
-        Ok(Some(res))
-    }
 }
 
-// impl Callable for NativeFn {
-//     fn arity(&self, interpreter: &Interpreter) -> usize {
-//         self.arity
-//     }
+impl Callable for NativeFn {
+    fn name(&self) -> &str {
+        &self.name
+    }
 
-// }
+    fn arity(&self, _interpreter: &Interpreter) -> Arity {
+        Arity::exactly(self.arity)
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        (self.func)(interpreter, args)
+    }
+}
 
 impl fmt::Debug for NativeFn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -67,8 +71,8 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
             arity: 0,
             func: |_, _| {
                 let start_time = SystemTime::now();
-                Ok(Value::Number(
-                    start_time.duration_since(UNIX_EPOCH).unwrap().as_millis() as f64,
+                Ok(Value::Integer(
+                    start_time.duration_since(UNIX_EPOCH).unwrap().as_millis() as i64,
                 ))
             },
         })),
@@ -79,17 +83,17 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
             name: "manere".to_string(),
             arity: 1,
             func: |_, args| {
-                if args.len() != 1 {
-                    let emsg = format!("Attempted to call manere with {} args but expected 1", args.len());
-                    return Err(emsg);
-                }
                 let arg = args.first().unwrap();
                 match arg {
+                    Value::Integer(x) => {
+                        thread::sleep(time::Duration::from_secs((*x).max(0) as u64));
+                        return Ok(Value::Empty);
+                    },
                     Value::Number(x) => {
                         thread::sleep(time::Duration::from_secs(x.round() as u64));
                         return Ok(Value::Empty);
                     },
-                    _ => { return Err(String::from("Attempted to call manere with a non-numeric argument")) }
+                    _ => { return Err(RuntimeError::new("type", String::from("Attempted to call manere with a non-numeric argument"), None)) }
                 }
             },
         })),
@@ -106,7 +110,7 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
                         return Ok(Value::String(read.trim_end().to_string()));
                     },
                     Err(e) => {
-                        return Err(format!("Encountered error reading line from stdin! {}", e.to_string()));
+                        return Err(RuntimeError::new("io", format!("Encountered error reading line from stdin! {}", e.to_string()), None));
                     },
                 }
             },
@@ -118,19 +122,21 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
             name: "legere".to_string(),
             arity: 1,
             func: |_, args| {
-                if args.len() != 1 {
-                    let emsg = format!("Attempted to call legere with {} args but expected 1", args.len());
-                    return Err(emsg);
-                }
                 let arg = args.first().unwrap();
                 match arg {
                     Value::String(x) => {
-                        let emsg = format!("Unable to read file at {}", x);
-                        let read = fs::read_to_string(x).expect(&emsg);
-                        return Ok(Value::String(read));
+                        match fs::read_to_string(x) {
+                            Ok(read) => {
+                                return Ok(Value::String(read));
+                            },
+                            Err(e) => {
+                                let emsg = format!("Unable to read file at {}. {}", x, e.to_string());
+                                return Err(RuntimeError::new("io", emsg, None));
+                            },
+                        }
                     }
                     _ => {
-                        return Err(String::from("Attempted to call legere with a non-string argument!"));
+                        return Err(RuntimeError::new("type", String::from("Attempted to call legere with a non-string argument!"), None));
                     }
                 }
             },
@@ -142,13 +148,27 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
             name: String::from("mutare"),
             arity: 1,
             func: |_, args| {
-                if args.len() != 1 {
-                    let emsg = format!("Attempted to call mutare with {} args but only expected 1", args.len());
-                    return Err(emsg);
-                }
                 let arg = args.first().unwrap();
                 match arg {
                     Value::String(x) => {
+                        // Whole numbers convert to integers, fractions like
+                        // 3/4 to rationals, anything else to a float.
+                        if let Ok(y) = x.parse::<i64>() {
+                            return Ok(Value::Integer(y));
+                        }
+                        if let Some(y) = BigInt::parse(x, 10) {
+                            return Ok(Value::BigInt(Rc::new(y)));
+                        }
+                        if let Some((num, den)) = x.split_once('/') {
+                            let parts = BigInt::parse(num.trim(), 10).zip(BigInt::parse(den.trim(), 10));
+                            return match parts.and_then(|(num, den)| Rational::new(num, den)) {
+                                Some(y) => Ok(numeric::from_rational(y)),
+                                None => {
+                                    let emsg = format!("Error converting {} to a Number. It isn't a valid fraction", arg);
+                                    Err(RuntimeError::new("type", emsg, None))
+                                }
+                            };
+                        }
                         let result = x.parse::<f64>();
                         match result {
                             Ok(y) => {
@@ -156,17 +176,17 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
                             },
                             Err(e) => {
                                 let emsg = format!("Error convering {} to a Number. Error was {}", arg, e.to_string());
-                                return Err(emsg);
+                                return Err(RuntimeError::new("type", emsg, None));
                             },
                         }
                     },
-                    Value::Number(x) => {
-                        let result = format!("{}", x);
+                    Value::Integer(_) | Value::BigInt(_) | Value::Rational(_) | Value::Number(_) => {
+                        let result = format!("{}", arg);
                         return Ok(Value::String(result));
                     },
                     _ => {
                         let emsg = format!("No conversion possible for this type. CAlled mutare with {}", arg);
-                        return Err(emsg);
+                        return Err(RuntimeError::new("type", emsg, None));
                     }
                 }
             },
@@ -179,11 +199,6 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
             arity: 2,
             func: |_, args| {
                 dbg!(args.clone());
-                if args.len() != 2 {
-                    let emsg = format!("Attempted to call mutare with {} args but only expected 1", args.len());
-                    return Err(emsg);
-                }
-
                 let path = match args.first() {
                     Some(p) => {
                         match p {
@@ -192,13 +207,13 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
                             },
                             _ => {
                                 let emsg = format!("The first argument to salvare should be a string! Got {}", p);
-                                return Err(emsg);
+                                return Err(RuntimeError::new("type", emsg, None));
                             }
                         }
                     },
                     None => {
                         let emsg = format!("Salvare takes 2 arguments");
-                        return Err(emsg);
+                        return Err(RuntimeError::new("argument", emsg, None));
                     },
                 };
 
@@ -211,13 +226,13 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
                             },
                             _ => {
                                 let emsg = format!("The second argument to salvare should be a string! Got {}", p);
-                                return Err(emsg);
+                                return Err(RuntimeError::new("type", emsg, None));
                             }
                         }
                     },
                     None => {
                         let emsg = format!("Salvare takes 2 arguments");
-                        return Err(emsg);
+                        return Err(RuntimeError::new("argument", emsg, None));
                     },
                 };
 
@@ -227,11 +242,193 @@ pub fn generate_native_functions() -> HashMap<String, Option<Value>> {
                     },
                     Err(e) => {
                         let emsg = format!("Encountered error writing file. {}", e.to_string());
-                        return Err(emsg);
+                        return Err(RuntimeError::new("io", emsg, None));
                     },
                 }
             }
         }))
     );
+    funcs.insert(
+        String::from("longitudo"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("longitudo"),
+            arity: 1,
+            func: |_, args| {
+                match args.first().unwrap() {
+                    Value::List(l) => Ok(Value::Integer(l.borrow().len() as i64)),
+                    Value::Map(m) => Ok(Value::Integer(m.borrow().len() as i64)),
+                    Value::String(x) => Ok(Value::Integer(x.chars().count() as i64)),
+                    other => {
+                        let emsg = format!("Attempted to take the length of {}, which is not a list, map or string", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("addere"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("addere"),
+            arity: 2,
+            func: |_, args| {
+                match args.first().unwrap() {
+                    Value::List(l) => {
+                        l.borrow_mut().push(args[1].clone());
+                        return Ok(Value::Empty);
+                    },
+                    other => {
+                        let emsg = format!("The first argument to addere should be a list! Got {}", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("demere"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("demere"),
+            arity: 1,
+            func: |_, args| {
+                match args.first().unwrap() {
+                    Value::List(l) => match l.borrow_mut().pop() {
+                        Some(v) => Ok(v),
+                        None => Err(RuntimeError::new("index", String::from("Attempted to call demere on an empty list"), None)),
+                    },
+                    other => {
+                        let emsg = format!("The argument to demere should be a list! Got {}", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("secare"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("secare"),
+            arity: 3,
+            func: |_, args| {
+                let list = match args.first().unwrap() {
+                    Value::List(l) => l.borrow(),
+                    other => {
+                        let emsg = format!("The first argument to secare should be a list! Got {}", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                };
+                // The end of a slice is exclusive, so it may be one past the
+                // last element.
+                let mut bounds: Vec<usize> = Vec::new();
+                for arg in &args[1..] {
+                    match Interpreter::to_index(arg, list.len() + 1) {
+                        Ok(i) => {
+                            bounds.push(i);
+                        },
+                        Err(_) => {
+                            let emsg = format!("{} is not a valid bound to slice a list of length {}", arg, list.len());
+                            return Err(RuntimeError::new("index", emsg, None));
+                        }
+                    }
+                }
+                if bounds[0] > bounds[1] {
+                    let emsg = format!("Attempted to slice from {} to {}, but the start is after the end", bounds[0], bounds[1]);
+                    return Err(RuntimeError::new("index", emsg, None));
+                }
+                let slice = list[bounds[0]..bounds[1]].to_vec();
+                Ok(Value::List(Rc::new(RefCell::new(slice))))
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("claves"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("claves"),
+            arity: 1,
+            func: |_, args| {
+                match args.first().unwrap() {
+                    Value::Map(m) => {
+                        // Maps have no order of their own, so hand the keys
+                        // back sorted to keep iteration predictable.
+                        let map = m.borrow();
+                        let mut keys: Vec<&MapKey> = map.keys().collect();
+                        keys.sort();
+                        let keys: Vec<Value> = keys.iter().map(|k| k.to_value()).collect();
+                        Ok(Value::List(Rc::new(RefCell::new(keys))))
+                    },
+                    other => {
+                        let emsg = format!("The argument to claves should be a map! Got {}", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("habet"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("habet"),
+            arity: 2,
+            func: |_, args| {
+                match args.first().unwrap() {
+                    Value::Map(m) => {
+                        let key = MapKey::from_value(&args[1]).map_err(|e| RuntimeError::new("type", e, None))?;
+                        Ok(Value::Bool(m.borrow().contains_key(&key)))
+                    },
+                    other => {
+                        let emsg = format!("The first argument to habet should be a map! Got {}", other);
+                        return Err(RuntimeError::new("type", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("romanus"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("romanus"),
+            arity: 1,
+            func: |_, args| {
+                let arg = args.first().unwrap();
+                let n = match arg {
+                    Value::Integer(x) => u64::try_from(*x).ok(),
+                    _ => None,
+                };
+                match n.and_then(roman::format) {
+                    Some(numeral) => Ok(Value::String(numeral)),
+                    None => {
+                        let emsg = format!("Only whole numbers from 1 to {} can be written in Roman numerals! Got {}", roman::MAX, arg);
+                        return Err(RuntimeError::new("argument", emsg, None));
+                    }
+                }
+            },
+        }))
+    );
+    funcs.insert(
+        String::from("ratio"),
+        Some(Value::NativeFn(NativeFn {
+            name: String::from("ratio"),
+            arity: 2,
+            func: |_, args| {
+                let mut parts: Vec<BigInt> = Vec::new();
+                for arg in &args {
+                    match arg {
+                        Value::Integer(x) => parts.push(BigInt::from_i64(*x)),
+                        Value::BigInt(x) => parts.push((**x).clone()),
+                        other => {
+                            let emsg = format!("The arguments to ratio should be whole numbers! Got {}", other);
+                            return Err(RuntimeError::new("type", emsg, None));
+                        }
+                    }
+                }
+                let den = parts.pop().unwrap();
+                let num = parts.pop().unwrap();
+                match Rational::new(num, den) {
+                    Some(x) => Ok(numeric::from_rational(x)),
+                    None => Err(RuntimeError::new("arithmetic", format!("Attempted to make a ratio with a denominator of zero! Got {}", args[1]), None)),
+                }
+            },
+        }))
+    );
     funcs
 }
@@ -0,0 +1,309 @@
+//! Arithmetic and comparison of numbers.
+//!
+//! Numbers form a tower, integers, big integers, rationals and floats. All but
+//! floats are exact. An operation is done at the level of whichever operand is
+//! highest up the tower, so mixing anything with a float gives a float, and
+//! mixing an integer with a rational gives a rational. Dividing integers with
+//! / or raising them to a negative power gives a rational, unless the result
+//! is a whole number.
+//!
+//! Integers that would overflow an i64 become big integers instead, and
+//! results are always brought back down the tower when they fit, so a big
+//! integer is never small enough to be an integer, and a rational is never
+//! a whole number.
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::{
+    ast::Value, bigint::BigInt, interpreter::RuntimeError, rational::Rational, token::Token,
+    tokentype::TokenType,
+};
+
+/// Apply an arithmetic or comparison operator to two numbers.
+pub fn binary(oper: &Token, left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+    if !is_number(left) || !is_number(right) {
+        let emsg = format!(
+            "Attempted to apply {} to {} and {}, but they are not both numbers. Line {}",
+            oper.lexeme, left, right, oper.line
+        );
+        return Err(RuntimeError::new("type", emsg, Some(oper.line)));
+    }
+    if let TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual =
+        oper.ttype
+    {
+        return Ok(compare(oper, order(left, right)));
+    }
+    match (left, right) {
+        (Value::Integer(l), Value::Integer(r)) => integer_binary(oper, *l, *r),
+        (Value::Number(_), _) | (_, Value::Number(_)) => {
+            float_binary(oper, as_float(left).unwrap(), as_float(right).unwrap())
+        }
+        (Value::Rational(_), _) | (_, Value::Rational(_)) => rational_binary(
+            oper,
+            &as_rational(left).unwrap(),
+            &as_rational(right).unwrap(),
+        ),
+        _ => bigint_binary(oper, &as_bigint(left).unwrap(), &as_bigint(right).unwrap()),
+    }
+}
+
+/// Negate a number, or None if the value isn't a number.
+pub fn negate(val: &Value) -> Option<Value> {
+    match val {
+        Value::Integer(x) => Some(match x.checked_neg() {
+            Some(x) => Value::Integer(x),
+            None => from_bigint(-&BigInt::from_i64(*x)),
+        }),
+        Value::BigInt(x) => Some(from_bigint(-&**x)),
+        Value::Rational(x) => Some(Value::Rational(Rc::new(-&**x))),
+        Value::Number(x) => Some(Value::Number(-x)),
+        _ => None,
+    }
+}
+
+/// Numbers are equal when they have the same value, whatever their type, so
+/// 1 == 1.0 and 0.5 == ratio(1, 2). None if either value isn't a number.
+pub fn equal(left: &Value, right: &Value) -> Option<bool> {
+    if is_number(left) && is_number(right) {
+        Some(order(left, right) == Some(Ordering::Equal))
+    } else {
+        None
+    }
+}
+
+/// Compare the values of two numbers exactly, whatever their type. None if
+/// either is NaN, or not a number at all.
+pub fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Integer(l), Value::Integer(r)) => Some(l.cmp(r)),
+        (Value::Number(l), Value::Number(r)) => l.partial_cmp(r),
+        (Value::Number(f), other) => order_float(*f, other),
+        (other, Value::Number(f)) => order_float(*f, other).map(Ordering::reverse),
+        _ => Some(as_rational(left)?.cmp(&as_rational(right)?)),
+    }
+}
+
+/// Compare a float to an exact number.
+fn order_float(f: f64, exact: &Value) -> Option<Ordering> {
+    // Integers up to 2^53 are exactly representable, so we can skip building
+    // a rational for the common case.
+    if let Value::Integer(i) = exact {
+        if i.unsigned_abs() <= 1 << 53 {
+            return f.partial_cmp(&(*i as f64));
+        }
+    }
+    if f.is_nan() {
+        return None;
+    }
+    match Rational::from_f64(f) {
+        Some(f) => Some(f.cmp(&as_rational(exact)?)),
+        // Must be infinite.
+        None => Some(if f > 0.0 {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }),
+    }
+}
+
+/// The integer a float is equal to, if it is a whole number that fits.
+pub fn float_to_integer(x: f64) -> Option<i64> {
+    // i64::MAX isn't exactly representable, but 2^63 is, and is one too big.
+    if x.fract() == 0.0 && x >= i64::MIN as f64 && x < i64::MAX as f64 {
+        Some(x as i64)
+    } else {
+        None
+    }
+}
+
+/// The exact number a float is equal to, or None if it's infinite or NaN.
+pub fn float_to_exact(x: f64) -> Option<Value> {
+    Rational::from_f64(x).map(from_rational)
+}
+
+/// A big integer as a value, as an Integer if it fits.
+pub fn from_bigint(x: BigInt) -> Value {
+    match x.to_i64() {
+        Some(i) => Value::Integer(i),
+        None => Value::BigInt(Rc::new(x)),
+    }
+}
+
+/// A rational as a value, as an integer if it's a whole number.
+pub fn from_rational(x: Rational) -> Value {
+    if x.is_integer() {
+        from_bigint(x.numerator().clone())
+    } else {
+        Value::Rational(Rc::new(x))
+    }
+}
+
+fn is_number(val: &Value) -> bool {
+    matches!(
+        val,
+        Value::Integer(_) | Value::BigInt(_) | Value::Rational(_) | Value::Number(_)
+    )
+}
+
+fn as_float(val: &Value) -> Option<f64> {
+    match val {
+        Value::Integer(x) => Some(*x as f64),
+        Value::BigInt(x) => Some(x.to_f64()),
+        Value::Rational(x) => Some(x.to_f64()),
+        Value::Number(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn as_bigint(val: &Value) -> Option<BigInt> {
+    match val {
+        Value::Integer(x) => Some(BigInt::from_i64(*x)),
+        Value::BigInt(x) => Some((**x).clone()),
+        _ => None,
+    }
+}
+
+fn as_rational(val: &Value) -> Option<Rational> {
+    match val {
+        Value::Rational(x) => Some((**x).clone()),
+        _ => as_bigint(val).map(Rational::from_integer),
+    }
+}
+
+fn integer_binary(oper: &Token, l: i64, r: i64) -> Result<Value, RuntimeError> {
+    // Anything that overflows is done again with big integers.
+    let checked = |res: Option<i64>| match res {
+        Some(x) => Ok(Value::Integer(x)),
+        None => bigint_binary(oper, &BigInt::from_i64(l), &BigInt::from_i64(r)),
+    };
+    match oper.ttype {
+        TokenType::Plus => checked(l.checked_add(r)),
+        TokenType::Minus => checked(l.checked_sub(r)),
+        TokenType::Star => checked(l.checked_mul(r)),
+        TokenType::Slash | TokenType::Backslash | TokenType::Percent if r == 0 => {
+            Err(divide_by_zero(oper, &l.to_string(), &r.to_string()))
+        }
+        // Dividing two integers doesn't have to give an integer, so / gives
+        // an exact rational when it doesn't. \ is the integer division.
+        TokenType::Slash => bigint_binary(oper, &BigInt::from_i64(l), &BigInt::from_i64(r)),
+        TokenType::Backslash => checked(l.checked_div(r)),
+        TokenType::Percent => checked(l.checked_rem(r)),
+        TokenType::StarStar if r < 0 => rational_binary(
+            oper,
+            &Rational::from_integer(BigInt::from_i64(l)),
+            &Rational::from_integer(BigInt::from_i64(r)),
+        ),
+        TokenType::StarStar => checked(u32::try_from(r).ok().and_then(|r| l.checked_pow(r))),
+        _ => Err(invalid_operator(oper)),
+    }
+}
+
+fn bigint_binary(oper: &Token, l: &BigInt, r: &BigInt) -> Result<Value, RuntimeError> {
+    match oper.ttype {
+        TokenType::Plus => Ok(from_bigint(l + r)),
+        TokenType::Minus => Ok(from_bigint(l - r)),
+        TokenType::Star => Ok(from_bigint(l * r)),
+        TokenType::Slash | TokenType::Backslash | TokenType::Percent if r.is_zero() => {
+            Err(divide_by_zero(oper, &l.to_string(), &r.to_string()))
+        }
+        TokenType::Slash => Ok(from_rational(Rational::new(l.clone(), r.clone()).unwrap())),
+        TokenType::Backslash => Ok(from_bigint(l.div_rem(r).unwrap().0)),
+        TokenType::Percent => Ok(from_bigint(l.div_rem(r).unwrap().1)),
+        TokenType::StarStar if r.is_negative() => rational_binary(
+            oper,
+            &Rational::from_integer(l.clone()),
+            &Rational::from_integer(r.clone()),
+        ),
+        TokenType::StarStar => match r.to_u32() {
+            Some(r) => Ok(from_bigint(l.pow(r))),
+            None => Err(too_large(oper, &l.to_string(), &r.to_string())),
+        },
+        _ => Err(invalid_operator(oper)),
+    }
+}
+
+fn rational_binary(oper: &Token, l: &Rational, r: &Rational) -> Result<Value, RuntimeError> {
+    match oper.ttype {
+        TokenType::Plus => Ok(from_rational(l + r)),
+        TokenType::Minus => Ok(from_rational(l - r)),
+        TokenType::Star => Ok(from_rational(l * r)),
+        TokenType::Slash | TokenType::Backslash | TokenType::Percent if r.is_zero() => {
+            Err(divide_by_zero(oper, &l.to_string(), &r.to_string()))
+        }
+        TokenType::Slash => Ok(from_rational(l / r)),
+        // Truncating, to match integer division.
+        TokenType::Backslash => Ok(from_bigint((l / r).trunc())),
+        TokenType::Percent => {
+            let quotient = Rational::from_integer((l / r).trunc());
+            Ok(from_rational(l - &(r * &quotient)))
+        }
+        // Only whole powers of a rational are rational.
+        TokenType::StarStar if !r.is_integer() => Ok(Value::Number(l.to_f64().powf(r.to_f64()))),
+        TokenType::StarStar => match r.numerator().to_i64() {
+            Some(exp) => match l.pow(exp) {
+                Some(x) => Ok(from_rational(x)),
+                None if l.is_zero() => Err(divide_by_zero(oper, &l.to_string(), &r.to_string())),
+                None => Err(too_large(oper, &l.to_string(), &r.to_string())),
+            },
+            None => Err(too_large(oper, &l.to_string(), &r.to_string())),
+        },
+        _ => Err(invalid_operator(oper)),
+    }
+}
+
+fn float_binary(oper: &Token, l: f64, r: f64) -> Result<Value, RuntimeError> {
+    match oper.ttype {
+        TokenType::Plus => Ok(Value::Number(l + r)),
+        TokenType::Minus => Ok(Value::Number(l - r)),
+        TokenType::Star => Ok(Value::Number(l * r)),
+        TokenType::Slash | TokenType::Backslash | TokenType::Percent if r == 0.0 => {
+            Err(divide_by_zero(oper, &l.to_string(), &r.to_string()))
+        }
+        TokenType::Slash => Ok(Value::Number(l / r)),
+        // Integer division truncates towards zero, which keeps it in step
+        // with %, so that a == b * (a \ b) + a % b.
+        TokenType::Backslash => Ok(Value::Number((l / r).trunc())),
+        TokenType::Percent => Ok(Value::Number(l % r)),
+        TokenType::StarStar => Ok(Value::Number(l.powf(r))),
+        _ => Err(invalid_operator(oper)),
+    }
+}
+
+/// Apply a comparison operator to the order of two numbers. Nothing compares
+/// to NaN, so every comparison with it is false.
+fn compare(oper: &Token, order: Option<Ordering>) -> Value {
+    let result = match order {
+        Some(order) => match oper.ttype {
+            TokenType::Greater => order.is_gt(),
+            TokenType::GreaterEqual => order.is_ge(),
+            TokenType::Less => order.is_lt(),
+            _ => order.is_le(),
+        },
+        None => false,
+    };
+    Value::Bool(result)
+}
+
+fn invalid_operator(oper: &Token) -> RuntimeError {
+    let emsg = format!(
+        "Attempted to evaluate an invalid binary expression with {}. Line {}",
+        oper.lexeme, oper.line
+    );
+    RuntimeError::new("runtime", emsg, Some(oper.line))
+}
+
+fn divide_by_zero(oper: &Token, l: &str, r: &str) -> RuntimeError {
+    let emsg = format!(
+        "Attempted to divide by zero!. Expression was {} {} {}. Line {}",
+        l, oper.lexeme, r, oper.line
+    );
+    RuntimeError::new("arithmetic", emsg, Some(oper.line))
+}
+
+fn too_large(oper: &Token, l: &str, r: &str) -> RuntimeError {
+    let emsg = format!(
+        "The exponent is too large to compute {} {} {}. Line {}",
+        l, oper.lexeme, r, oper.line
+    );
+    RuntimeError::new("arithmetic", emsg, Some(oper.line))
+}
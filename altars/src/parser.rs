@@ -1,6 +1,8 @@
 //! A Hand-written recurisve descent parser for Daemonica.
+use std::collections::HashMap;
+
 use crate::{
-    ast::{ASTNode, Expr, Stmt},
+    ast::{ASTNode, Expr, Param, Stmt},
     literals::Literal,
     token::Token,
     tokentype::TokenType,
@@ -15,12 +17,25 @@ pub struct Parser {
     tokens: Vec<Token>,
     /// Store a counter that indexes to the current token.
     current: usize,
+    /// How many loops deep we currently are, so that we can reject frange and
+    /// perge when they aren't inside of one.
+    loop_depth: usize,
+    /// The names declared in each enclosing scope, and whether they are
+    /// constants, so that assigning to a constant is caught before anything
+    /// runs. Names declared somewhere we can't see (like the builtins, or an
+    /// earlier line of the repl) are left for the interpreter to check.
+    scopes: Vec<HashMap<String, bool>>,
 }
 
 impl Parser {
     /// Initialize a parser instance with some input set of tokens.
     pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            loop_depth: 0,
+            scopes: vec![HashMap::new()],
+        }
     }
 
     /// Given an initialized Parser; produces a series of AST Nodes to be interpreted.
@@ -58,32 +73,103 @@ impl Parser {
     /// Top level of our parse tree. Checks for function definitions, or variable
     /// declarations first. If neither descends into statement.
     fn declaration(&mut self) -> Stmt {
-        if self.maybe_advance(vec![TokenType::Fn]) {
+        if self.maybe_advance(vec![TokenType::Class]) {
+            return self.class_decl();
+        }
+        // An incantatio without a name is an anonymous function, which is an
+        // expression, so we leave it for statement to pick up.
+        if self.check(TokenType::Fn) && self.peek_next().ttype == TokenType::Identifier {
+            self.next();
+            self.declare(&self.peek(), false);
             return self.function();
         }
         if self.maybe_advance(vec![TokenType::Var]) {
             return self.var_decl();
         }
+        if self.maybe_advance(vec![TokenType::Const]) {
+            return self.const_decl();
+        }
         return self.statement();
     }
 
+    /// Grab the name of the daemonium, it's parent if it has one, and each of the
+    /// methods declared inside of it. Methods are declared just like any other incantatio.
+    fn class_decl(&mut self) -> Stmt {
+        let name = self.consume(TokenType::Identifier);
+        self.declare(&name, false);
+        let mut superclass = None;
+        if self.maybe_advance(vec![TokenType::Less]) {
+            superclass = Some(Expr::Variable(self.consume(TokenType::Identifier)));
+        }
+        self.consume(TokenType::LeftBrace);
+        let mut methods: Vec<Stmt> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.at_end() {
+            self.consume(TokenType::Fn);
+            methods.push(self.function());
+        }
+        self.consume(TokenType::RightBrace);
+        Stmt::Class(name, superclass, methods)
+    }
+
     /// Grab the function name, and the actual body of the function.
     fn function(&mut self) -> Stmt {
         let name = self.consume(TokenType::Identifier);
+        let (params, body) = self.function_body();
+        Stmt::Function(name, params, body)
+    }
+
+    /// The parameter list and body, shared by declared and anonymous functions.
+    ///
+    /// Parameters with default values have to come after the ones without, and
+    /// a rest parameter can only be the very last one.
+    fn function_body(&mut self) -> (Vec<Param>, Vec<Stmt>) {
         self.consume(TokenType::LeftParen);
-        let mut params: Vec<Token> = Vec::new();
+        self.scopes.push(HashMap::new());
+        let mut params: Vec<Param> = Vec::new();
         if self.check(TokenType::RightParen) == false {
             loop {
-                params.push(self.consume(TokenType::Identifier));
+                if self.maybe_advance(vec![TokenType::DotDotDot]) {
+                    let name = self.consume(TokenType::Identifier);
+                    if !self.check(TokenType::RightParen) {
+                        panic!(
+                            "The rest parameter {} must be the last parameter, on line {}",
+                            name.lexeme, name.line
+                        );
+                    }
+                    params.push(Param {
+                        name,
+                        default: None,
+                        rest: true,
+                    });
+                    break;
+                }
+                let mut param = Param::new(self.consume(TokenType::Identifier));
+                if self.maybe_advance(vec![TokenType::Equal]) {
+                    param.default = Some(self.expression());
+                } else if params.iter().any(|p| p.default.is_some()) {
+                    panic!(
+                        "Parameter {} without a default follows one with a default, on line {}",
+                        param.name.lexeme, param.name.line
+                    );
+                }
+                params.push(param);
                 if self.maybe_advance(vec![TokenType::Comma]) == false {
                     break;
                 }
             }
         }
         self.consume(TokenType::RightParen);
+        for param in &params {
+            self.declare(&param.name, false);
+        }
         self.consume(TokenType::LeftBrace);
+        // A loop around the declaration doesn't make it's body part of the loop.
+        let enclosing_depth = self.loop_depth;
+        self.loop_depth = 0;
         let body = self.block();
-        Stmt::Function(name, params, body)
+        self.loop_depth = enclosing_depth;
+        self.scopes.pop();
+        (params, body)
     }
 
     /// Collect the variable name, then we need to see if we have an initializer
@@ -93,6 +179,7 @@ impl Parser {
     fn var_decl(&mut self) -> Stmt {
         // Grab the variable name first of all, so we can bind it
         let name = self.consume(TokenType::Identifier);
+        self.declare(&name, false);
 
         // Check if we have an initializer statement or not. If we do we need
         // to parse that out as an expression.
@@ -107,11 +194,51 @@ impl Parser {
         }
     }
 
+    /// constans are just like ligamen, except that they have to be initialized,
+    /// and can't be assigned to afterwards.
+    fn const_decl(&mut self) -> Stmt {
+        let name = self.consume(TokenType::Identifier);
+        if !self.maybe_advance(vec![TokenType::Equal]) {
+            panic!(
+                "The constant {} needs a value, on line {}",
+                name.lexeme, name.line
+            );
+        }
+        let initializer = self.expression();
+        self.consume(TokenType::Semicolon);
+        self.declare(&name, true);
+        Stmt::Const(name, initializer)
+    }
+
+    /// Record a declaration in the current scope. Nothing can be declared
+    /// over a constant in the same scope.
+    fn declare(&mut self, name: &Token, constant: bool) {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.get(&name.lexeme) == Some(&true) {
+            panic!(
+                "{} is already a constant in this scope, on line {}",
+                name.lexeme, name.line
+            );
+        }
+        scope.insert(name.lexeme.clone(), constant);
+    }
+
+    /// The nearest declaration of a name decides if it can be assigned to.
+    fn check_assignable(&self, name: &Token) {
+        let nearest = self.scopes.iter().rev().find_map(|s| s.get(&name.lexeme));
+        if nearest == Some(&true) {
+            panic!(
+                "Can't assign to {}, since it's a constant, on line {}",
+                name.lexeme, name.line
+            );
+        }
+    }
+
     /// 2nd Level of the parser.
     ///
     /// This level has multiple productions of the same precidence.
     /// Print and Return statements are both "detected" here, as are
-    /// flow control statements like While, If and For.
+    /// flow control statements like While, If, For, Break and Continue.
     /// Block Statements are also parsed here.
     fn statement(&mut self) -> Stmt {
         if self.maybe_advance(vec![TokenType::Print]) {
@@ -132,9 +259,79 @@ impl Parser {
         if self.maybe_advance(vec![TokenType::For]) {
             return self.parse_for();
         }
+        if self.maybe_advance(vec![TokenType::Break, TokenType::Continue]) {
+            return self.parse_loop_jump();
+        }
+        if self.maybe_advance(vec![TokenType::Try]) {
+            return self.try_stmt();
+        }
+        if self.maybe_advance(vec![TokenType::Throw]) {
+            return self.throw_stmt();
+        }
+        if self.maybe_advance(vec![TokenType::Call]) {
+            return self.import_stmt();
+        }
         return self.expression_stmt();
     }
 
+    /// conare { ... } capere (err) { ... }. Both parts have to be blocks, and
+    /// there is always exactly one capere.
+    fn try_stmt(&mut self) -> Stmt {
+        self.consume(TokenType::LeftBrace);
+        let body = self.block();
+        self.consume(TokenType::Catch);
+        self.consume(TokenType::LeftParen);
+        let name = self.consume(TokenType::Identifier);
+        self.consume(TokenType::RightParen);
+        self.consume(TokenType::LeftBrace);
+        self.scopes.push(HashMap::new());
+        self.declare(&name, false);
+        let handler = self.block();
+        self.scopes.pop();
+        Stmt::Try(body, name, handler)
+    }
+
+    /// invocabo "lib/astra.rit"; The path has to be a plain string, since we
+    /// don't want to have to run anything to find out what to load.
+    fn import_stmt(&mut self) -> Stmt {
+        let keyword = self.previous();
+        let path = self.consume(TokenType::String);
+        self.consume(TokenType::Semicolon);
+        Stmt::Import(keyword, path.literal.to_string())
+    }
+
+    fn throw_stmt(&mut self) -> Stmt {
+        let keyword = self.previous();
+        let value = self.expression();
+        self.consume(TokenType::Semicolon);
+        Stmt::Throw(keyword, value)
+    }
+
+    /// frange (break) and perge (continue) only make sense inside of a loop.
+    fn parse_loop_jump(&mut self) -> Stmt {
+        let keyword = self.previous();
+        if self.loop_depth == 0 {
+            panic!(
+                "{} used outside of a loop on line {}",
+                keyword.lexeme, keyword.line
+            );
+        }
+        self.consume(TokenType::Semicolon);
+        match keyword.ttype {
+            TokenType::Break => Stmt::Break(keyword),
+            _ => Stmt::Continue(keyword),
+        }
+    }
+
+    /// Parse the body of a dum or enim loop, keeping track of the fact that
+    /// we are inside of one.
+    fn loop_body(&mut self) -> Stmt {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
+    }
+
     /// Take the right hand side as an expression and dump it wholesale
     /// into a new AST node.
     fn parse_print(&mut self) -> Stmt {
@@ -145,8 +342,6 @@ impl Parser {
 
     /// Grab the right hand side of the expression, and throw it into the AST.
     /// The RHS in the AST node is an Option so that empty returns are allowed.
-    ///
-    /// ***NOTE: Return DOES NOT return control flow, merley a value.***
     fn parse_return(&mut self) -> Stmt {
         let prev = self.previous();
         let mut value = None;
@@ -159,11 +354,13 @@ impl Parser {
 
     /// This produces a vector of statements, that we then wrap inside a [crate::ast::Stmt::Block].
     fn block(&mut self) -> Vec<Stmt> {
+        self.scopes.push(HashMap::new());
         let mut stmts = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.at_end() {
             stmts.push(self.declaration());
         }
         self.consume(TokenType::RightBrace);
+        self.scopes.pop();
         stmts
     }
 
@@ -173,8 +370,8 @@ impl Parser {
         self.consume(TokenType::LeftParen);
         let condition = self.expression();
         self.consume(TokenType::RightParen);
-        let body = self.statement();
-        return Stmt::While(condition, Box::new(body));
+        let body = self.loop_body();
+        return Stmt::While(condition, Box::new(body), None);
     }
 
     /// This handles both if and else constructs.
@@ -199,13 +396,17 @@ impl Parser {
     /// As any for loop
     /// can also be expressed as a while loop, with some extra code that runs
     /// before execution (initializer), and some code that runs
-    /// before each iteration (incrementation etc).
+    /// after each iteration (incrementation etc).
     ///
     /// As such we can; instead of implementing
     /// the for loop itself; break the expression into it's components right now
     /// avoiding adding an explicit case to the interpreter, and another variant in our AST.
+    /// The increment is kept on the While node itself rather than in the body,
+    /// so that it still runs when the body is cut short by perge.
     fn parse_for(&mut self) -> Stmt {
         self.consume(TokenType::LeftParen);
+        // The initializer is scoped to the loop.
+        self.scopes.push(HashMap::new());
         // Parse out the various parts of our for statement, for desugaring
         // in a second
 
@@ -233,28 +434,21 @@ impl Parser {
         self.consume(TokenType::RightParen);
 
         // And finally get the body of the statement.
-        let mut body = self.statement();
-
-        match increment {
-            // If we have an increment, then create the AST Node for it.
-            Some(inc) => {
-                body = Stmt::Block(vec![body, Stmt::Expression(inc)]);
-                match cond {
-                    Some(_) => {}
-                    None => {
-                        cond = Some(Expr::Literal(Literal::Bool(true)));
-                    }
-                }
-                body = Stmt::While(cond.unwrap(), Box::new(body));
-
-                match initializer {
-                    Some(init) => {
-                        body = Stmt::Block(vec![init, body]);
-                    }
-                    None => {}
-                }
+        let body = self.loop_body();
+        self.scopes.pop();
+
+        // No condition means loop forever.
+        let cond = match cond {
+            Some(c) => c,
+            None => Expr::Literal(Literal::Bool(true)),
+        };
+        let mut body = Stmt::While(cond, Box::new(body), increment);
+
+        match initializer {
+            Some(init) => {
+                body = Stmt::Block(vec![init, body]);
             }
-            _ => {}
+            None => {}
         }
         body
     }
@@ -276,7 +470,7 @@ impl Parser {
 
     /// Parse varaible assignment
     fn assignment(&mut self) -> Expr {
-        let expr = self.parse_or();
+        let expr = self.conditional();
 
         if self.maybe_advance(vec![TokenType::Equal]) {
             // If we have an assignment, then grab the value
@@ -287,8 +481,17 @@ impl Parser {
             // we bind the value we just got, to that name.
             match expr {
                 Expr::Variable(tok) => {
+                    self.check_assignable(&tok);
                     return Expr::Assign(tok, Box::new(value));
                 }
+                // Assigning to a property of an instance.
+                Expr::Get(object, name) => {
+                    return Expr::Set(object, name, Box::new(value));
+                }
+                // Assigning to an element of a list.
+                Expr::Index(object, bracket, index) => {
+                    return Expr::SetIndex(object, bracket, index, Box::new(value));
+                }
                 // If not, we forgot to actually give the assignment it's RHS.
                 _ => {
                     let emsg = format!("{} is an invalid assignment target", equals);
@@ -296,9 +499,68 @@ impl Parser {
                 }
             }
         }
+
+        if self.maybe_advance(vec![
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            let compound = self.previous();
+            let oper = Parser::compound_operator(&compound);
+            let value = Box::new(self.assignment());
+            match expr {
+                // a += 1 is just a = a + 1, since looking up a variable twice
+                // can't do anything surprising.
+                Expr::Variable(tok) => {
+                    self.check_assignable(&tok);
+                    let current = Box::new(Expr::Variable(tok.clone()));
+                    return Expr::Assign(tok, Box::new(Expr::Binary(current, oper, value)));
+                }
+                // Whereas the object and index could be calls, so they get
+                // their own node that only evaluates them once.
+                Expr::Get(_, _) | Expr::Index(_, _, _) => {
+                    return Expr::Update(Box::new(expr), oper, value);
+                }
+                _ => {
+                    let emsg = format!("{} is an invalid assignment target", compound);
+                    panic!("{}", emsg);
+                }
+            }
+        }
         return expr;
     }
 
+    /// The binary operator that a compound assignment applies, eg. + for +=.
+    fn compound_operator(compound: &Token) -> Token {
+        let ttype = match compound.ttype {
+            TokenType::PlusEqual => TokenType::Plus,
+            TokenType::MinusEqual => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            _ => TokenType::Percent,
+        };
+        let lexeme = compound.lexeme.trim_end_matches('=').to_string();
+        Token::new(ttype, lexeme, Literal::Empty, compound.line)
+    }
+
+    /// Parse conditional expressions, cond ? a : b
+    ///
+    /// These nest to the right, so a ? b : c ? d : e picks between b, d and e
+    /// like a chain of si / aliter would.
+    fn conditional(&mut self) -> Expr {
+        let cond = self.parse_or();
+
+        if self.maybe_advance(vec![TokenType::Question]) {
+            let then = self.expression();
+            self.consume(TokenType::Colon);
+            let otherwise = self.conditional();
+            return Expr::Conditional(Box::new(cond), Box::new(then), Box::new(otherwise));
+        }
+        cond
+    }
+
     /// parse logical ors
     ///
     /// Quite simple, grab the RHS and the LHS, throw them in a node.
@@ -365,10 +627,15 @@ impl Parser {
         expr
     }
 
-    /// Parse * and / expressions
+    /// Parse *, /, \ (integer division) and % expressions
     fn factor(&mut self) -> Expr {
         let mut expr = self.unary();
-        while self.maybe_advance(vec![TokenType::Star, TokenType::Slash]) {
+        while self.maybe_advance(vec![
+            TokenType::Star,
+            TokenType::Slash,
+            TokenType::Backslash,
+            TokenType::Percent,
+        ]) {
             let op = self.previous();
             let right = self.unary();
             expr = Expr::Binary(Box::new(expr), op, Box::new(right));
@@ -386,13 +653,26 @@ impl Parser {
             let right = self.unary();
             Expr::Unary(op, Box::new(right))
         } else {
-            // If it's not a unary expr, then we might have a call
-            self.call()
-            //self.primary()
+            // If it's not a unary expr, then we might have a power or a call
+            self.power()
+        }
+    }
+
+    /// Parse ** expressions. These bind tighter than unary operators, so
+    /// -2 ** 2 is -(2 ** 2), and are right associative, so 2 ** 3 ** 2 is
+    /// 2 ** (3 ** 2). The exponent goes back through unary so that 2 ** -1
+    /// still works.
+    fn power(&mut self) -> Expr {
+        let expr = self.call();
+        if self.maybe_advance(vec![TokenType::StarStar]) {
+            let op = self.previous();
+            let right = self.unary();
+            return Expr::Binary(Box::new(expr), op, Box::new(right));
         }
+        expr
     }
 
-    /// This handles function calls.
+    /// This handles function calls, property access and indexing.
     /// Extracts the name of the function, and (possibly) arguments
     /// and produces a new node with them.
     fn call(&mut self) -> Expr {
@@ -400,6 +680,14 @@ impl Parser {
         loop {
             if self.maybe_advance(vec![TokenType::LeftParen]) {
                 expr = self.parse_arglist(expr);
+            } else if self.maybe_advance(vec![TokenType::Dot]) {
+                let name = self.consume(TokenType::Identifier);
+                expr = Expr::Get(Box::new(expr), name);
+            } else if self.maybe_advance(vec![TokenType::LeftBracket]) {
+                let bracket = self.previous();
+                let index = self.expression();
+                self.consume(TokenType::RightBracket);
+                expr = Expr::Index(Box::new(expr), bracket, Box::new(index));
             } else {
                 break;
             }
@@ -407,22 +695,36 @@ impl Parser {
         return expr;
     }
 
+    /// Arguments can be passed by name, as `name: value`, but only once all of
+    /// the positional arguments have been given.
     fn parse_arglist(&mut self, callee: Expr) -> Expr {
         let mut args: Vec<Expr> = Vec::new();
+        let mut named: Vec<(Token, Expr)> = Vec::new();
         if self.check(TokenType::RightParen) == false {
             loop {
-                args.push(self.expression());
+                if self.check(TokenType::Identifier) && self.peek_next().ttype == TokenType::Colon {
+                    let name = self.next();
+                    self.next();
+                    named.push((name, self.expression()));
+                } else if let Some((name, _)) = named.last() {
+                    panic!(
+                        "Positional argument follows the named argument {} on line {}",
+                        name.lexeme, name.line
+                    );
+                } else {
+                    args.push(self.expression());
+                }
                 if !self.maybe_advance(vec![TokenType::Comma]) {
                     break;
                 }
             }
         }
         let paren = self.consume(TokenType::RightParen);
-        Expr::Call(Box::new(callee), paren, args)
+        Expr::Call(Box::new(callee), paren, args, named)
     }
 
     /// Bottom case is primary expressions.
-    /// This includes all literals, variables and paren groupings.
+    /// This includes all literals, list and map literals, variables and paren groupings.
     /// If we reach here, and nothing is valid then we've bottomed out
     /// the parser, and need to report an error to the user.
     fn primary(&mut self) -> Expr {
@@ -441,18 +743,96 @@ impl Parser {
         if self.maybe_advance(vec![TokenType::String]) {
             return Expr::Literal(self.previous().literal);
         }
+        if self.maybe_advance(vec![TokenType::Interpolation]) {
+            return self.interpolation();
+        }
         if self.maybe_advance(vec![TokenType::Identifier]) {
             return Expr::Variable(self.previous());
         }
+        if self.maybe_advance(vec![TokenType::Self_]) {
+            return Expr::Self_(self.previous());
+        }
+        if self.maybe_advance(vec![TokenType::Super]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Dot);
+            let method = self.consume(TokenType::Identifier);
+            return Expr::Super(keyword, method);
+        }
 
         if self.maybe_advance(vec![TokenType::LeftParen]) {
             let expr = self.expression();
             self.consume(TokenType::RightParen);
             return Expr::Grouping(Box::new(expr));
         }
+        if self.maybe_advance(vec![TokenType::Fn]) {
+            let keyword = self.previous();
+            let (params, body) = self.function_body();
+            return Expr::Lambda(keyword, params, body);
+        }
+        if self.maybe_advance(vec![TokenType::LeftBracket]) {
+            return self.list();
+        }
+        if self.maybe_advance(vec![TokenType::LeftBrace]) {
+            return self.map();
+        }
         panic!("Bottomed out of Parser::primary with {}", self.peek());
     }
 
+    /// List literals are a comma sepperated list of expressions between []
+    fn list(&mut self) -> Expr {
+        let mut items: Vec<Expr> = Vec::new();
+        if self.check(TokenType::RightBracket) == false {
+            loop {
+                items.push(self.expression());
+                if !self.maybe_advance(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightBracket);
+        Expr::List(items)
+    }
+
+    /// Interpolated strings alternate between pieces of text and expressions.
+    /// Every expression is followed by another Interpolation token if there is
+    /// more to come, or by the String token that finishes the literal.
+    fn interpolation(&mut self) -> Expr {
+        let start = self.previous();
+        let mut parts: Vec<Expr> = vec![Expr::Literal(start.literal.clone())];
+        loop {
+            parts.push(self.expression());
+            if self.maybe_advance(vec![TokenType::Interpolation]) {
+                parts.push(Expr::Literal(self.previous().literal));
+            } else {
+                parts.push(Expr::Literal(self.consume(TokenType::String).literal));
+                break;
+            }
+        }
+        Expr::Interpolate(start, parts)
+    }
+
+    /// Map literals are a comma sepperated list of key: value pairs between {}
+    ///
+    /// A { at the start of a statement is always a block, so map literals can
+    /// only appear where we are already expecting an expression.
+    fn map(&mut self) -> Expr {
+        let brace = self.previous();
+        let mut pairs: Vec<(Expr, Expr)> = Vec::new();
+        if self.check(TokenType::RightBrace) == false {
+            loop {
+                let key = self.expression();
+                self.consume(TokenType::Colon);
+                let value = self.expression();
+                pairs.push((key, value));
+                if !self.maybe_advance(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightBrace);
+        Expr::Map(brace, pairs)
+    }
+
     // Helper functions that abstract out common logic.
 
     /// Consume the next token, if it matches the provided token variant.
@@ -504,6 +884,14 @@ impl Parser {
         self.tokens.get(self.current).unwrap().clone()
     }
 
+    /// Lookahead two tokens.
+    fn peek_next(&self) -> Token {
+        match self.tokens.get(self.current + 1) {
+            Some(tok) => tok.clone(),
+            None => self.tokens.last().unwrap().clone(),
+        }
+    }
+
     /// Return true if the next token is EOF.
     fn at_end(&self) -> bool {
         self.peek().ttype == TokenType::EOF
@@ -602,7 +990,7 @@ mod tests {
                     Literal::Empty,
                     3,
                 ),
-                Some(Expr::Literal(Literal::Number(5.0))),
+                Some(Expr::Literal(Literal::Integer(5))),
             )],
         ))];
         assert!(expected == res);
@@ -670,9 +1058,9 @@ mod tests {
         );
         let expected = vec![ASTNode::StmtNode(Stmt::If(
             Expr::Binary(
-                Box::new(Expr::Literal(Literal::Number(1.0))),
+                Box::new(Expr::Literal(Literal::Integer(1))),
                 Token::new(TokenType::EqualEqual, String::from("=="), Literal::Empty, 2),
-                Box::new(Expr::Literal(Literal::Number(2.0))),
+                Box::new(Expr::Literal(Literal::Integer(2))),
             ),
             Box::new(Stmt::Block(vec![Stmt::Print(Expr::Literal(
                 Literal::StrLit(String::from("Maths is hard")),
@@ -688,7 +1076,7 @@ mod tests {
         let test = String::from("5 + (20 + 2 * (3));");
         let res = process(test);
         let expected = vec![ASTNode::StmtNode(Stmt::Expression(Expr::Binary(
-            Box::new(Expr::Literal(Literal::Number(5.0))),
+            Box::new(Expr::Literal(Literal::Integer(5))),
             Token {
                 ttype: TokenType::Plus,
                 lexeme: "+".to_string(),
@@ -696,7 +1084,7 @@ mod tests {
                 line: 1,
             },
             Box::new(Expr::Grouping(Box::new(Expr::Binary(
-                Box::new(Expr::Literal(Literal::Number(20.0))),
+                Box::new(Expr::Literal(Literal::Integer(20))),
                 Token {
                     ttype: TokenType::Plus,
                     lexeme: "+".to_string(),
@@ -704,16 +1092,14 @@ mod tests {
                     line: 1,
                 },
                 Box::new(Expr::Binary(
-                    Box::new(Expr::Literal(Literal::Number(2.0))),
+                    Box::new(Expr::Literal(Literal::Integer(2))),
                     Token {
                         ttype: TokenType::Star,
                         lexeme: "*".to_string(),
                         literal: Literal::Empty,
                         line: 1,
                     },
-                    Box::new(Expr::Grouping(Box::new(Expr::Literal(Literal::Number(
-                        3.0,
-                    ))))),
+                    Box::new(Expr::Grouping(Box::new(Expr::Literal(Literal::Integer(3))))),
                 )),
             )))),
         )))];
@@ -752,9 +1138,9 @@ mod tests {
         );
         let expected = vec![ASTNode::StmtNode(Stmt::If(
             Expr::Binary(
-                Box::new(Expr::Literal(Literal::Number(1.0))),
+                Box::new(Expr::Literal(Literal::Integer(1))),
                 Token::new(TokenType::EqualEqual, String::from("=="), Literal::Empty, 2),
-                Box::new(Expr::Literal(Literal::Number(2.0))),
+                Box::new(Expr::Literal(Literal::Integer(2))),
             ),
             Box::new(Stmt::Block(vec![Stmt::Print(Expr::Literal(
                 Literal::StrLit(String::from("Maths is hard")),
@@ -785,7 +1171,7 @@ mod tests {
                     literal: Literal::Empty,
                     line: 2,
                 },
-                Some(Expr::Literal(Literal::Number(1.0))),
+                Some(Expr::Literal(Literal::Integer(1))),
             ),
             Stmt::While(
                 Expr::Binary(
@@ -801,39 +1187,37 @@ mod tests {
                         literal: Literal::Empty,
                         line: 2,
                     },
-                    Box::new(Expr::Literal(Literal::Number(100.0))),
+                    Box::new(Expr::Literal(Literal::Integer(100))),
                 ),
-                Box::new(Stmt::Block(vec![
-                    Stmt::Block(vec![Stmt::Print(Expr::Variable(Token {
+                Box::new(Stmt::Block(vec![Stmt::Print(Expr::Variable(Token {
+                    ttype: TokenType::Identifier,
+                    lexeme: "i".to_string(),
+                    literal: Literal::Empty,
+                    line: 3,
+                }))])),
+                Some(Expr::Assign(
+                    Token {
                         ttype: TokenType::Identifier,
                         lexeme: "i".to_string(),
                         literal: Literal::Empty,
-                        line: 3,
-                    }))]),
-                    Stmt::Expression(Expr::Assign(
-                        Token {
+                        line: 2,
+                    },
+                    Box::new(Expr::Binary(
+                        Box::new(Expr::Variable(Token {
                             ttype: TokenType::Identifier,
                             lexeme: "i".to_string(),
                             literal: Literal::Empty,
                             line: 2,
+                        })),
+                        Token {
+                            ttype: TokenType::Plus,
+                            lexeme: "+".to_string(),
+                            literal: Literal::Empty,
+                            line: 2,
                         },
-                        Box::new(Expr::Binary(
-                            Box::new(Expr::Variable(Token {
-                                ttype: TokenType::Identifier,
-                                lexeme: "i".to_string(),
-                                literal: Literal::Empty,
-                                line: 2,
-                            })),
-                            Token {
-                                ttype: TokenType::Plus,
-                                lexeme: "+".to_string(),
-                                literal: Literal::Empty,
-                                line: 2,
-                            },
-                            Box::new(Expr::Literal(Literal::Number(1.0))),
-                        )),
+                        Box::new(Expr::Literal(Literal::Integer(1))),
                     )),
-                ])),
+                )),
             ),
         ]))];
         assert!(res == expected);
@@ -858,7 +1242,7 @@ mod tests {
                     literal: Literal::Empty,
                     line: 2,
                 },
-                Some(Expr::Literal(Literal::Number(0.0))),
+                Some(Expr::Literal(Literal::Integer(0))),
             )),
             ASTNode::StmtNode(Stmt::While(
                 Expr::Binary(
@@ -874,7 +1258,7 @@ mod tests {
                         literal: Literal::Empty,
                         line: 3,
                     },
-                    Box::new(Expr::Literal(Literal::Number(100.0))),
+                    Box::new(Expr::Literal(Literal::Integer(100))),
                 ),
                 Box::new(Stmt::Block(vec![Stmt::Expression(Expr::Assign(
                     Token {
@@ -896,9 +1280,10 @@ mod tests {
                             literal: Literal::Empty,
                             line: 4,
                         },
-                        Box::new(Expr::Literal(Literal::Number(1.0))),
+                        Box::new(Expr::Literal(Literal::Integer(1))),
                     )),
                 ))])),
+                None,
             )),
         ];
         assert!(res == expected);
@@ -926,6 +1311,253 @@ mod tests {
         assert!(orres == expectedor);
     }
 
+    #[test]
+    fn classdecl() {
+        let test = String::from(
+            r#"daemonium Familiar {
+              incantatio salve() {
+                beneficium anima.nomen;
+              }
+            }"#,
+        );
+        let res = process(test);
+        let expected = vec![ASTNode::StmtNode(Stmt::Class(
+            Token::new(
+                TokenType::Identifier,
+                String::from("Familiar"),
+                Literal::Empty,
+                1,
+            ),
+            None,
+            vec![Stmt::Function(
+                Token::new(
+                    TokenType::Identifier,
+                    String::from("salve"),
+                    Literal::Empty,
+                    2,
+                ),
+                vec![],
+                vec![Stmt::Return(
+                    Token::new(
+                        TokenType::Return,
+                        String::from("beneficium"),
+                        Literal::Empty,
+                        3,
+                    ),
+                    Some(Expr::Get(
+                        Box::new(Expr::Self_(Token::new(
+                            TokenType::Self_,
+                            String::from("anima"),
+                            Literal::Empty,
+                            3,
+                        ))),
+                        Token::new(
+                            TokenType::Identifier,
+                            String::from("nomen"),
+                            Literal::Empty,
+                            3,
+                        ),
+                    )),
+                )],
+            )],
+        ))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn break_outside_loop() {
+        process(String::from("frange;"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn continue_inside_function_inside_loop() {
+        process(String::from("dum(verum) { incantatio f() { perge; } }"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn conare_needs_capere() {
+        process(String::from("conare { 1; }"));
+    }
+
+    #[test]
+    fn constants() {
+        let res = process(String::from("constans x = 1; incantatio f(x) { x = 2; }"));
+        assert!(matches!(&res[0], ASTNode::StmtNode(Stmt::Const(name, _)) if name.lexeme == "x"));
+        for test in [
+            "constans x = 1; x = 2;",
+            "constans x = 1; { { x = 2; } }",
+            "constans x = 1; incantatio f() { x = 3; }",
+            "constans x = 1; ligamen x = 2;",
+            "constans x;",
+        ] {
+            let result = std::panic::catch_unwind(|| process(String::from(test)));
+            assert!(result.is_err(), "{} should not parse", test);
+        }
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let a = Token::new(TokenType::Identifier, String::from("a"), Literal::Empty, 1);
+        let res = process(String::from("a *= 2;"));
+        let expected = vec![ASTNode::StmtNode(Stmt::Expression(Expr::Assign(
+            a.clone(),
+            Box::new(Expr::Binary(
+                Box::new(Expr::Variable(a)),
+                Token::new(TokenType::Star, String::from("*"), Literal::Empty, 1),
+                Box::new(Expr::Literal(Literal::Integer(2))),
+            )),
+        )))];
+        assert!(res == expected);
+        let res = process(String::from("xs[0] -= 1; p.x %= 3;"));
+        assert!(
+            matches!(&res[0], ASTNode::StmtNode(Stmt::Expression(Expr::Update(target, oper, _)))
+            if matches!(**target, Expr::Index(_, _, _)) && oper.ttype == TokenType::Minus)
+        );
+        assert!(
+            matches!(&res[1], ASTNode::StmtNode(Stmt::Expression(Expr::Update(target, _, _)))
+            if matches!(**target, Expr::Get(_, _)))
+        );
+        for test in ["constans x = 1; x += 2;", "1 += 2;"] {
+            let result = std::panic::catch_unwind(|| process(String::from(test)));
+            assert!(result.is_err(), "{} should not parse", test);
+        }
+    }
+
+    #[test]
+    fn conditional() {
+        let var = |name: &str| {
+            Box::new(Expr::Variable(Token::new(
+                TokenType::Identifier,
+                String::from(name),
+                Literal::Empty,
+                1,
+            )))
+        };
+        let res = process(String::from("x = a vel b ? c : d ? e : f;"));
+        let expected = vec![ASTNode::StmtNode(Stmt::Expression(Expr::Assign(
+            Token::new(TokenType::Identifier, String::from("x"), Literal::Empty, 1),
+            Box::new(Expr::Conditional(
+                Box::new(Expr::Logic(
+                    var("a"),
+                    Token::new(TokenType::Or, String::from("vel"), Literal::Empty, 1),
+                    var("b"),
+                )),
+                var("c"),
+                Box::new(Expr::Conditional(var("d"), var("e"), var("f"))),
+            )),
+        )))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    fn index_assignment() {
+        let res = process(String::from("xs[0] = [1];"));
+        let expected = vec![ASTNode::StmtNode(Stmt::Expression(Expr::SetIndex(
+            Box::new(Expr::Variable(Token::new(
+                TokenType::Identifier,
+                String::from("xs"),
+                Literal::Empty,
+                1,
+            ))),
+            Token::new(TokenType::LeftBracket, String::from("["), Literal::Empty, 1),
+            Box::new(Expr::Literal(Literal::Integer(0))),
+            Box::new(Expr::List(vec![Expr::Literal(Literal::Integer(1))])),
+        )))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    fn lambda() {
+        let test = String::from("ligamen f = incantatio (x) { beneficium x; };");
+        let res = process(test);
+        let x = Token::new(TokenType::Identifier, String::from("x"), Literal::Empty, 1);
+        let expected = vec![ASTNode::StmtNode(Stmt::Var(
+            Token::new(TokenType::Identifier, String::from("f"), Literal::Empty, 1),
+            Some(Expr::Lambda(
+                Token::new(TokenType::Fn, String::from("incantatio"), Literal::Empty, 1),
+                vec![Param::new(x.clone())],
+                vec![Stmt::Return(
+                    Token::new(
+                        TokenType::Return,
+                        String::from("beneficium"),
+                        Literal::Empty,
+                        1,
+                    ),
+                    Some(Expr::Variable(x)),
+                )],
+            )),
+        ))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    fn default_and_rest_params() {
+        let test = String::from("incantatio f(a, b = 2, ...c) {}");
+        let res = process(test);
+        let ident =
+            |name: &str| Token::new(TokenType::Identifier, String::from(name), Literal::Empty, 1);
+        let expected = vec![ASTNode::StmtNode(Stmt::Function(
+            ident("f"),
+            vec![
+                Param::new(ident("a")),
+                Param {
+                    name: ident("b"),
+                    default: Some(Expr::Literal(Literal::Integer(2))),
+                    rest: false,
+                },
+                Param {
+                    name: ident("c"),
+                    default: None,
+                    rest: true,
+                },
+            ],
+            vec![],
+        ))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rest_param_must_be_last() {
+        process(String::from("incantatio f(...a, b) {}"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn required_param_after_default() {
+        process(String::from("incantatio f(a = 1, b) {}"));
+    }
+
+    #[test]
+    fn named_args() {
+        let test = String::from("f(1, b: 2);");
+        let res = process(test);
+        let expected = vec![ASTNode::StmtNode(Stmt::Expression(Expr::Call(
+            Box::new(Expr::Variable(Token::new(
+                TokenType::Identifier,
+                String::from("f"),
+                Literal::Empty,
+                1,
+            ))),
+            Token::new(TokenType::RightParen, String::from(")"), Literal::Empty, 1),
+            vec![Expr::Literal(Literal::Integer(1))],
+            vec![(
+                Token::new(TokenType::Identifier, String::from("b"), Literal::Empty, 1),
+                Expr::Literal(Literal::Integer(2)),
+            )],
+        )))];
+        assert!(res == expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn positional_after_named() {
+        process(String::from("f(a: 1, 2);"));
+    }
+
     fn process(testcase: String) -> Vec<ASTNode> {
         let tok = Scanner::scan(testcase);
         Parser::parse(tok)
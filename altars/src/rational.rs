@@ -0,0 +1,217 @@
+//! Exact fractions, built on top of [BigInt].
+use core::fmt;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::bigint::BigInt;
+
+/// A fraction kept in lowest terms with a positive denominator, so every
+/// number has one representation and the derived Eq and Hash are correct.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl Rational {
+    /// None if the denominator is zero.
+    pub fn new(num: BigInt, den: BigInt) -> Option<Rational> {
+        if den.is_zero() {
+            return None;
+        }
+        let gcd = num.gcd(&den);
+        let (mut num, _) = num.div_rem(&gcd).unwrap();
+        let (mut den, _) = den.div_rem(&gcd).unwrap();
+        if den.is_negative() {
+            num = -&num;
+            den = -&den;
+        }
+        Some(Rational { num, den })
+    }
+
+    pub fn from_integer(x: BigInt) -> Rational {
+        Rational {
+            num: x,
+            den: BigInt::from_i64(1),
+        }
+    }
+
+    /// The exact value of a float. Every finite float is a fraction with a
+    /// power of two on the bottom, so this is always possible unless it's
+    /// infinite or NaN.
+    pub fn from_f64(x: f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return None;
+        }
+        let bits = x.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = (bits & ((1 << 52) - 1)) as i64;
+        // Subnormals don't have the implicit leading 1.
+        let (mantissa, exponent) = match exponent {
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), exponent - 1075),
+        };
+        let mantissa = BigInt::from_i64(if x < 0.0 { -mantissa } else { mantissa });
+        let two = BigInt::from_i64(2);
+        if exponent >= 0 {
+            Some(Rational::from_integer(
+                &mantissa * &two.pow(exponent as u32),
+            ))
+        } else {
+            Rational::new(mantissa, two.pow(-exponent as u32))
+        }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.num
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == BigInt::from_i64(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    /// The integer part, rounding towards zero.
+    pub fn trunc(&self) -> BigInt {
+        self.num.div_rem(&self.den).unwrap().0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Both halves could be too big for a float even when the fraction
+        // isn't, so work out the leading 25 digits of the fraction and let
+        // rust's float parsing do the rounding.
+        let digits = |x: &BigInt| x.abs().to_string().len() as i64;
+        let shift = 25 - (digits(&self.num) - digits(&self.den));
+        let ten = BigInt::from_i64(10);
+        let (leading, _) = if shift >= 0 {
+            (&self.num * &ten.pow(shift as u32)).div_rem(&self.den)
+        } else {
+            self.num.div_rem(&(&self.den * &ten.pow(-shift as u32)))
+        }
+        .unwrap();
+        format!("{}e{}", leading, -shift).parse().unwrap()
+    }
+
+    /// None when raising zero to a negative power, or when the exponent is
+    /// too large to compute.
+    pub fn pow(&self, exp: i64) -> Option<Rational> {
+        let n = u32::try_from(exp.unsigned_abs()).ok()?;
+        let (num, den) = (self.num.pow(n), self.den.pow(n));
+        if exp < 0 {
+            Rational::new(den, num)
+        } else {
+            Some(Rational { num, den })
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The denominators are positive, so cross multiplying keeps the order.
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        let num = &(&self.num * &other.den) + &(&other.num * &self.den);
+        Rational::new(num, &self.den * &other.den).unwrap()
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        self + &(-other)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.num, &self.den * &other.den).unwrap()
+    }
+}
+
+/// Dividing by zero panics, so check first.
+impl Div for &Rational {
+    type Output = Rational;
+
+    fn div(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.den, &self.den * &other.num)
+            .expect("Rational division by zero")
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            num: -&self.num,
+            den: self.den.clone(),
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(num: i64, den: i64) -> Rational {
+        Rational::new(BigInt::from_i64(num), BigInt::from_i64(den)).unwrap()
+    }
+
+    #[test]
+    fn lowest_terms() {
+        assert!(ratio(6, -8).to_string() == "-3/4");
+        assert!(ratio(0, 5) == ratio(0, -3));
+        assert!(Rational::new(BigInt::from_i64(1), BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert!(&ratio(1, 3) + &ratio(1, 6) == ratio(1, 2));
+        assert!(&ratio(1, 3) - &ratio(1, 2) == ratio(-1, 6));
+        assert!(&ratio(2, 3) * &ratio(3, 4) == ratio(1, 2));
+        assert!(&ratio(2, 3) / &ratio(-4, 3) == ratio(-1, 2));
+        assert!(ratio(2, 3).pow(-2) == Some(ratio(9, 4)));
+        assert!(ratio(-7, 2).trunc() == BigInt::from_i64(-3));
+        assert!(ratio(1, 3) < ratio(1, 2));
+        assert!(ratio(-1, 2) < ratio(-1, 3));
+    }
+
+    #[test]
+    fn floats() {
+        assert!(Rational::from_f64(0.75) == Some(ratio(3, 4)));
+        assert!(Rational::from_f64(-2.0) == Some(ratio(-2, 1)));
+        assert!(Rational::from_f64(f64::NAN).is_none());
+        assert!(ratio(1, 4).to_f64() == 0.25);
+        let tiny = Rational::from_f64(f64::MIN_POSITIVE).unwrap();
+        assert!(tiny.to_f64() == f64::MIN_POSITIVE);
+    }
+}
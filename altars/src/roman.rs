@@ -0,0 +1,88 @@
+//! Conversion to and from Roman numerals.
+
+/// Each numeral, along with the subtractive pairs, from largest to smallest.
+const NUMERALS: [(u64, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// The largest number that can be written without a vinculum.
+pub const MAX: u64 = 3999;
+
+/// Write a number as a Roman numeral. There is no numeral for 0, and nothing
+/// past [MAX], so those come back as None.
+pub fn format(mut n: u64) -> Option<String> {
+    if n == 0 || n > MAX {
+        return None;
+    }
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
+        }
+    }
+    Some(out)
+}
+
+/// Read a Roman numeral. Only the canonical form of a number is accepted, so
+/// IIII, IC and VV are all rejected.
+///
+/// Rather than encoding all of the rules, we read the numeral greedily and
+/// then check that writing the number back out gives the same text.
+pub fn parse(text: &str) -> Option<u64> {
+    let mut rest = text;
+    let mut n = 0;
+    for (value, numeral) in NUMERALS {
+        while let Some(remaining) = rest.strip_prefix(numeral) {
+            n += value;
+            rest = remaining;
+        }
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    match format(n) {
+        Some(canonical) if canonical == text => Some(n),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for n in 1..=MAX {
+            assert!(parse(&format(n).unwrap()) == Some(n));
+        }
+    }
+
+    #[test]
+    fn known_numerals() {
+        assert!(parse("MCMXCIV") == Some(1994));
+        assert!(parse("MMXXIV") == Some(2024));
+        assert!(format(49) == Some(String::from("XLIX")));
+        assert!(format(0).is_none());
+        assert!(format(4000).is_none());
+    }
+
+    #[test]
+    fn non_canonical() {
+        for text in ["", "IIII", "IC", "VV", "XM", "MMMM", "IIX", "ABC"] {
+            assert!(parse(text).is_none());
+        }
+    }
+}
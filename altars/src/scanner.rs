@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use substring::Substring;
 
 use lazy_static::*;
 
+use crate::bigint::BigInt;
 use crate::literals::Literal;
+use crate::roman;
 use crate::token::Token;
 use crate::tokentype::TokenType;
 
@@ -28,6 +31,8 @@ enum ScanError {
     UnterminatedBlockComment(usize),
     UnterminatedString(usize),
     NumParseError((usize, String)),
+    InvalidEscape((usize, String)),
+    InvalidInterpolation((usize, String)),
 }
 
 lazy_static! {
@@ -47,13 +52,19 @@ lazy_static! {
         m.insert("beneficium".to_string(), TokenType::Return);
         m.insert("enim".to_string(), TokenType::For);
         m.insert("dum".to_string(), TokenType::While);
+        m.insert("frange".to_string(), TokenType::Break);
+        m.insert("perge".to_string(), TokenType::Continue);
         m.insert("nihil".to_string(), TokenType::None);
         m.insert("anima".to_string(), TokenType::Self_);
         m.insert("ligamen".to_string(), TokenType::Var);
+        m.insert("constans".to_string(), TokenType::Const);
         m.insert("daemonium".to_string(), TokenType::Class);
         m.insert("cognatio".to_string(), TokenType::Super);
         m.insert("invocabo".to_string(), TokenType::Call);
         m.insert("scribo".to_string(), TokenType::Print);
+        m.insert("conare".to_string(), TokenType::Try);
+        m.insert("capere".to_string(), TokenType::Catch);
+        m.insert("iacere".to_string(), TokenType::Throw);
         return m;
     };
 }
@@ -112,6 +123,20 @@ impl Scanner {
                         eprintln!("Lexer encountered invalid number {} at line {}", x.1, x.0);
                         had_error = true;
                     }
+                    ScanError::InvalidEscape(x) => {
+                        eprintln!(
+                            "Lexer encountered invalid escape sequence {} at line {}",
+                            x.1, x.0
+                        );
+                        had_error = true;
+                    }
+                    ScanError::InvalidInterpolation(x) => {
+                        eprintln!(
+                            "Lexer encountered invalid interpolation {} at line {}",
+                            x.1, x.0
+                        );
+                        had_error = true;
+                    }
                 },
             }
         }
@@ -159,26 +184,59 @@ impl Scanner {
             '}' => {
                 self.add_token(TokenType::RightBrace);
             }
+            '[' => {
+                self.add_token(TokenType::LeftBracket);
+            }
+            ']' => {
+                self.add_token(TokenType::RightBracket);
+            }
             ';' => {
                 self.add_token(TokenType::Semicolon);
             }
             ',' => {
                 self.add_token(TokenType::Comma);
             }
+            ':' => {
+                self.add_token(TokenType::Colon);
+            }
+            '?' => {
+                self.add_token(TokenType::Question);
+            }
             '.' => {
-                self.add_token(TokenType::Dot);
+                if self.peek() == '.' && self.peek_n(1) == '.' {
+                    self.next();
+                    self.next();
+                    self.add_token(TokenType::DotDotDot);
+                } else {
+                    self.add_token(TokenType::Dot);
+                }
             }
-            '*' => {
-                self.add_token(TokenType::Star);
+            '\\' => {
+                self.add_token(TokenType::Backslash);
+            }
+            // We need lookahead on these, so we look 1c ahead for thse pesky
+            // compoud operations.
+            '%' => {
+                if self.match_next('=') {
+                    self.add_token(TokenType::PercentEqual);
+                } else {
+                    self.add_token(TokenType::Percent);
+                }
             }
             '-' => {
-                self.add_token(TokenType::Minus);
+                if self.match_next('=') {
+                    self.add_token(TokenType::MinusEqual);
+                } else {
+                    self.add_token(TokenType::Minus);
+                }
             }
             '+' => {
-                self.add_token(TokenType::Plus);
+                if self.match_next('=') {
+                    self.add_token(TokenType::PlusEqual);
+                } else {
+                    self.add_token(TokenType::Plus);
+                }
             }
-            // We need lookahead on these, so we look 1c ahead for thse pesky
-            // compoud operations.
             '!' => {
                 if self.match_next('=') {
                     self.add_token(TokenType::BangEqual)
@@ -186,6 +244,15 @@ impl Scanner {
                     self.add_token(TokenType::Bang)
                 }
             }
+            '*' => {
+                if self.match_next('*') {
+                    self.add_token(TokenType::StarStar);
+                } else if self.match_next('=') {
+                    self.add_token(TokenType::StarEqual);
+                } else {
+                    self.add_token(TokenType::Star);
+                }
+            }
             '=' => {
                 if self.match_next('=') {
                     self.add_token(TokenType::EqualEqual);
@@ -232,16 +299,26 @@ impl Scanner {
                     self.next();
                     self.next();
                     self.next();
+                } else if self.match_next('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             }
             '"' => {
-                self.lex_string()?;
+                self.lex_string(false)?;
+            }
+            // Raw strings, where backslashes are just backslashes.
+            'r' if self.peek() == '"' => {
+                self.next();
+                self.lex_string(true)?;
             }
             '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
                 self.lex_number()?;
             }
+            '#' => {
+                self.lex_roman()?;
+            }
 
             ' ' => {}
             '\r' => {}
@@ -263,63 +340,321 @@ impl Scanner {
     /// Keep peeking until our next token is a closing ", adding each char to a string.
     /// If the "" is unterminated, we report an error. Otherwise we return
     /// the lexed string.
-    fn lex_string(&mut self) -> Result<(), ScanError> {
-        while self.peek() != '"' && self.at_end() == false {
-            if self.peek() == '\n' {
-                self.line += 1;
-            }
-
+    ///
+    /// Strings opened with """ run until the next """, and so can hold lone
+    /// quotes. Any string can span several lines, and we count them as we go.
+    /// Escape sequences are processed unless the string is raw (r"...").
+    ///
+    /// Unless the string is raw, {expr} interpolates an expression into it. Each
+    /// piece of text before an interpolation becomes an Interpolation token,
+    /// followed by the tokens of the expression, and the final piece is an
+    /// ordinary String token. So "Ave, {nomen}!" is lexed as
+    /// Interpolation("Ave, ") Identifier(nomen) String("!").
+    ///
+    /// This means a brace in an ordinary string always starts an
+    /// interpolation, so a literal brace has to be escaped as \{ (and \} to
+    /// match), or the string written raw.
+    fn lex_string(&mut self, raw: bool) -> Result<(), ScanError> {
+        let triple = self.peek() == '"' && self.peek_n(1) == '"';
+        if triple {
+            self.next();
             self.next();
         }
 
-        if self.at_end() {
-            return Err(ScanError::UnterminatedString(self.line));
+        let mut text = String::new();
+        // If we hit a bad escape we still want to find the end of the string,
+        // otherwise the rest of it gets lexed as if it were code.
+        let mut bad_escape: Option<String> = None;
+        loop {
+            if self.at_end() {
+                return Err(ScanError::UnterminatedString(self.line));
+            }
+            let c = self.next();
+            match c {
+                '"' if !triple => break,
+                '"' if self.peek() == '"' && self.peek_n(1) == '"' => {
+                    self.next();
+                    self.next();
+                    break;
+                }
+                '{' if !raw => {
+                    self.add_token_with_literal(TokenType::Interpolation, Literal::StrLit(text));
+                    text = String::new();
+                    self.lex_interpolated_expr()?;
+                }
+                '\\' if !raw => match self.lex_escape() {
+                    Ok(x) => text.push(x),
+                    Err(x) => {
+                        bad_escape.get_or_insert(x);
+                    }
+                },
+                '\n' => {
+                    self.line += 1;
+                    text.push(c);
+                }
+                _ => text.push(c),
+            }
         }
 
-        // Consume the closing "
-        self.next();
+        if let Some(x) = bad_escape {
+            return Err(ScanError::InvalidEscape((self.line, x)));
+        }
+        self.add_token_with_literal(TokenType::String, Literal::StrLit(text));
 
-        // Slice off the ""
-        let text = self
-            .src
-            .substring(self.start + 1, self.current - 1)
-            .to_string();
-        self.add_token_with_literal(TokenType::String, Literal::StrLit(text.clone()));
+        Ok(())
+    }
+
+    /// Scan the expression inside of an interpolation, up to the matching }.
+    ///
+    /// The expression gets a scanner of it's own, starting on the line the
+    /// expression starts on, so that the tokens (and any errors) carry the
+    /// right line numbers. It's tokens go straight into our token list.
+    fn lex_interpolated_expr(&mut self) -> Result<(), ScanError> {
+        let line = self.line;
+        let begin = self.current;
+        let mut depth = 0;
+        loop {
+            if self.at_end() {
+                let text = self.src.substring(begin - 1, self.current).to_string();
+                return Err(ScanError::InvalidInterpolation((line, text)));
+            }
+            match self.peek() {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                '\n' => self.line += 1,
+                // Skip over strings inside of the expression, so that their
+                // quotes don't end the string we are in.
+                '"' => {
+                    self.next();
+                    while self.peek() != '"' && !self.at_end() {
+                        match self.next() {
+                            '\n' => self.line += 1,
+                            '\\' if !self.at_end() => {
+                                self.next();
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+            if !self.at_end() {
+                self.next();
+            }
+        }
+        let src = self.src.substring(begin, self.current).to_string();
+        // Consume the closing }
+        self.next();
 
+        let mut nested = Scanner::new(src);
+        nested.line = line;
+        while !nested.at_end() {
+            nested.start = nested.current;
+            nested.scan_token()?;
+        }
+        if nested.tokens.is_empty() {
+            return Err(ScanError::InvalidInterpolation((line, String::from("{}"))));
+        }
+        self.tokens.append(&mut nested.tokens);
         Ok(())
     }
 
+    /// Translate the escape sequence following a \ into the character it stands
+    /// for. Unicode escapes are written as \u{...} with 1 to 6 hex digits.
+    /// If the escape is invalid we hand back the offending text.
+    fn lex_escape(&mut self) -> Result<char, String> {
+        if self.at_end() {
+            return Err(String::from("\\"));
+        }
+        let c = self.next();
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            // So that braces can be written without starting an interpolation.
+            '{' => Ok('{'),
+            '}' => Ok('}'),
+            'u' => {
+                let mut seq = String::from("\\u");
+                if !self.match_next('{') {
+                    return Err(seq);
+                }
+                seq.push('{');
+                while self.peek().is_ascii_hexdigit() {
+                    seq.push(self.next());
+                }
+                if !self.match_next('}') {
+                    return Err(seq);
+                }
+                seq.push('}');
+                let digits = &seq[3..seq.len() - 1];
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err(seq);
+                }
+                match u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    Some(x) => Ok(x),
+                    None => Err(seq),
+                }
+            }
+            _ => {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                Err(format!("\\{}", c))
+            }
+        }
+    }
+
     /// Simmialr to our string function, keep peeking while we have numeric parts.
     ///
     /// We need some special handling since we allow floats. As such we look 2-ahead
     /// after we break out of our first loop. If we have a number after it, we know that
     /// everything after is the fractional part.
     /// Another annoying edge case where we need to be 2-lookahead.
+    ///
+    /// Numbers may also be written in hex (0x1F) or binary (0b1010), have an
+    /// exponent (6.02e23), and use underscores between digits (1_000_000).
     fn lex_number(&mut self) -> Result<(), ScanError> {
-        while self.peek().is_numeric() {
+        let radix = match (self.src.chars().nth(self.start), self.peek()) {
+            (Some('0'), 'x' | 'X') => 16,
+            (Some('0'), 'b' | 'B') => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            // Consume the x or b
             self.next();
+            self.eat_number_chars();
+            let text = self.src.substring(self.start, self.current).to_string();
+            let digits = &text[2..];
+            if digits.is_empty() || !Scanner::valid_digits(digits, radix) {
+                return Err(ScanError::NumParseError((self.line, text)));
+            }
+            let literal = Scanner::integer_literal(digits, radix);
+            self.add_token_with_literal(TokenType::Number, literal);
+            return Ok(());
         }
 
+        self.eat_digits();
+
+        // Without a point or an exponent, the number is an integer.
+        let mut integer = true;
+
         // Do we have a decimal point?
-        if self.peek() == '.' && self.peek_n(1).is_numeric() {
+        if self.peek() == '.' && self.peek_n(1).is_ascii_digit() {
             // Consume the .
             self.next();
-            while self.peek().is_numeric() {
+            self.eat_digits();
+            integer = false;
+        }
+
+        // Or an exponent, which may have a sign.
+        let signed = matches!(self.peek_n(1), '+' | '-') && self.peek_n(2).is_ascii_digit();
+        if matches!(self.peek(), 'e' | 'E') && (self.peek_n(1).is_ascii_digit() || signed) {
+            self.next();
+            if signed {
                 self.next();
             }
+            self.eat_digits();
+            integer = false;
         }
 
-        let text = self.src.substring(self.start, self.current);
+        // Anything else stuck to the end of the number means it's malformed,
+        // eg. 1e or 12abc, so grab it so we can report the whole thing.
+        let end = self.current;
+        self.eat_number_chars();
+        let text = self.src.substring(self.start, self.current).to_string();
+        if self.current != end || !Scanner::valid_digits(&text, 10) {
+            return Err(ScanError::NumParseError((self.line, text)));
+        }
 
-        match text.parse::<f64>() {
+        if integer {
+            let literal = Scanner::integer_literal(&text, 10);
+            self.add_token_with_literal(TokenType::Number, literal);
+            return Ok(());
+        }
+
+        match text.replace('_', "").parse::<f64>() {
             Ok(x) => {
                 self.add_token_with_literal(TokenType::Number, Literal::Number(x));
                 Ok(())
             }
             Err(v) => {
                 eprintln!("{}", v);
-                return Err(ScanError::NumParseError((self.line, text.to_string())));
+                return Err(ScanError::NumParseError((self.line, text)));
+            }
+        }
+    }
+
+    /// Integers that are too big for an i64 become big integers. The digits
+    /// must already have been checked.
+    fn integer_literal(digits: &str, radix: u32) -> Literal {
+        let digits = digits.replace('_', "");
+        match i64::from_str_radix(&digits, radix) {
+            Ok(x) => Literal::Integer(x),
+            Err(_) => Literal::BigInt(Rc::new(BigInt::parse(&digits, radix).unwrap())),
+        }
+    }
+
+    /// Roman numerals are written after a #, eg. #MCMXCIV, so that they can't be
+    /// confused with identifiers. They have to be in canonical form.
+    fn lex_roman(&mut self) -> Result<(), ScanError> {
+        self.eat_number_chars();
+        let text = self.src.substring(self.start, self.current).to_string();
+        match roman::parse(&text[1..]) {
+            Some(x) => {
+                self.add_token_with_literal(TokenType::Number, Literal::Integer(x as i64));
+                Ok(())
             }
+            None => Err(ScanError::NumParseError((self.line, text))),
+        }
+    }
+
+    /// Consume decimal digits, and any underscores between them.
+    fn eat_digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.next();
+        }
+    }
+
+    /// Consume anything that could be part of a numeric literal.
+    fn eat_number_chars(&mut self) {
+        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+            self.next();
+        }
+    }
+
+    /// Underscores are only allowed between two digits, so 1__0, 1_ and 1_.5
+    /// are all rejected. Anything else in the text has to be a digit of the
+    /// radix, apart from the point and exponent of a decimal number.
+    fn valid_digits(text: &str, radix: u32) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        chars.iter().enumerate().all(|(i, c)| match c {
+            '_' => {
+                i > 0
+                    && i + 1 < chars.len()
+                    && chars[i - 1].is_digit(radix)
+                    && chars[i + 1].is_digit(radix)
+            }
+            '.' | 'e' | 'E' | '+' | '-' => radix == 10,
+            _ => c.is_digit(radix),
+        })
+    }
+
+    /// Whether name would lex as a single identifier.
+    pub fn is_identifier(name: &str) -> bool {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                chars.all(|c| c.is_ascii_alphanumeric()) && !KEYWORDS.contains_key(name)
+            }
+            _ => false,
         }
     }
 
@@ -427,24 +762,87 @@ mod tests {
         let test: String = "5;".to_string();
         let mut s: Scanner = Scanner::new(test);
         let tokens = s.scan_tokens();
-        let five = Token::new(TokenType::Number, "5".to_string(), Literal::Number(5.0), 1);
+        let five = Token::new(TokenType::Number, "5".to_string(), Literal::Integer(5), 1);
         let semicolon = Token::new(TokenType::Semicolon, ";".to_string(), Literal::Empty, 1);
         let eof = Token::new(TokenType::EOF, "".to_string(), Literal::Empty, 1);
         let expected = vec![five, semicolon, eof];
         assert!(expected == tokens);
     }
 
+    #[test]
+    fn extended_number_literals() {
+        let cases = [
+            ("0x1F", Literal::Integer(31)),
+            ("0XfF", Literal::Integer(255)),
+            ("0b1010", Literal::Integer(10)),
+            ("0b1111_0000", Literal::Integer(240)),
+            ("1_000_000", Literal::Integer(1000000)),
+            ("6.02e23", Literal::Number(6.02e23)),
+            ("1.5E-3", Literal::Number(0.0015)),
+            ("2e+2", Literal::Number(200.0)),
+            ("1_0.2_5", Literal::Number(10.25)),
+            (
+                "9223372036854775808",
+                Literal::BigInt(Rc::new(BigInt::parse("9223372036854775808", 10).unwrap())),
+            ),
+            (
+                "0x1_0000_0000_0000_0000",
+                Literal::BigInt(Rc::new(BigInt::parse("18446744073709551616", 10).unwrap())),
+            ),
+        ];
+        for (text, expected) in cases {
+            let tokens = Scanner::scan(text.to_string());
+            assert!(tokens[0].literal == expected);
+            assert!(tokens[0].lexeme == text);
+        }
+    }
+
+    #[test]
+    fn malformed_number_literals() {
+        let cases = [
+            "0x", "0b102", "0xG1", "1__0", "1_", "1_.5", "1e", "12abc", "0x_1",
+        ];
+        for text in cases {
+            let mut s = Scanner::new(text.to_string());
+            s.next();
+            match s.lex_number() {
+                Err(ScanError::NumParseError((line, bad))) => {
+                    assert!(line == 1);
+                    assert!(bad == text);
+                }
+                _ => panic!("{} should not lex as a number", text),
+            }
+        }
+    }
+
+    #[test]
+    fn roman_numerals() {
+        let tokens = Scanner::scan(String::from("#MCMXCIV;"));
+        let expected = Token::new(
+            TokenType::Number,
+            "#MCMXCIV".to_string(),
+            Literal::Integer(1994),
+            1,
+        );
+        assert!(tokens[0] == expected);
+        for text in ["#", "#IIII", "#mcm", "#XM"] {
+            let mut s = Scanner::new(text.to_string());
+            s.next();
+            assert!(s.lex_roman().is_err());
+        }
+    }
+
     #[test]
     fn parenthesized_exprs() {
         let test: String = "5 + (3 * (8));".to_string();
         let expected = vec![
-            Token::new(TokenType::Number, "5".to_string(), Literal::Number(5.0), 1),
+            Token::new(TokenType::Number, "5".to_string(), Literal::Integer(5), 1),
             Token::new(TokenType::Plus, "+".to_string(), Literal::Empty, 1),
             Token::new(TokenType::LeftParen, "(".to_string(), Literal::Empty, 1),
-            Token::new(TokenType::Number, "3".to_string(), Literal::Number(3.0), 1),
+            Token::new(TokenType::Number, "3".to_string(), Literal::Integer(3), 1),
             Token::new(TokenType::Star, "*".to_string(), Literal::Empty, 1),
             Token::new(TokenType::LeftParen, "(".to_string(), Literal::Empty, 1),
-            Token::new(TokenType::Number, "8".to_string(), Literal::Number(8.0), 1),
+            Token::new(TokenType::Number, "8".to_string(), Literal::Integer(8), 1),
             Token::new(TokenType::RightParen, ")".to_string(), Literal::Empty, 1),
             Token::new(TokenType::RightParen, ")".to_string(), Literal::Empty, 1),
             Token::new(TokenType::Semicolon, ";".to_string(), Literal::Empty, 1),
@@ -486,6 +884,38 @@ mod tests {
         assert!(expected == tokens);
     }
 
+    #[test]
+    fn arithmetic_operators() {
+        let test: String = "* ** % \\".to_string();
+        let expected = vec![
+            Token::new(TokenType::Star, "*".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::StarStar, "**".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::Percent, "%".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::Backslash, "\\".to_string(), Literal::Empty, 1),
+            get_eof(1),
+        ];
+        let mut s: Scanner = Scanner::new(test);
+        let tokens = s.scan_tokens();
+        assert!(expected == tokens);
+    }
+
+    #[test]
+    fn compound_assignment_operators() {
+        let test: String = "+= -= *= /= %= **".to_string();
+        let expected = vec![
+            Token::new(TokenType::PlusEqual, "+=".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::MinusEqual, "-=".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::StarEqual, "*=".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::SlashEqual, "/=".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::PercentEqual, "%=".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::StarStar, "**".to_string(), Literal::Empty, 1),
+            get_eof(1),
+        ];
+        let mut s: Scanner = Scanner::new(test);
+        let tokens = s.scan_tokens();
+        assert!(expected == tokens);
+    }
+
     #[test]
     fn scan_direct() {
         let test = String::from("ligamen a = 5;");
@@ -512,7 +942,7 @@ mod tests {
             Token {
                 ttype: TokenType::Number,
                 lexeme: "5".to_string(),
-                literal: Literal::Number(5.0),
+                literal: Literal::Integer(5),
                 line: 1,
             },
             get_semicolon(1),
@@ -584,6 +1014,116 @@ mod tests {
         crate::scanner::Scanner::scan(test);
     }
 
+    #[test]
+    fn string_escapes() {
+        let test = String::from(r#""a\tb\n\"c\" \\ \u{41}\u{1F600}";"#);
+        let tokens = Scanner::scan(test);
+        let expected = Literal::StrLit(String::from("a\tb\n\"c\" \\ A\u{1F600}"));
+        assert!(tokens[0].literal == expected);
+    }
+
+    #[test]
+    fn raw_strings() {
+        let test = String::from(r#"r"C:\new\tab";"#);
+        let tokens = Scanner::scan(test);
+        assert!(tokens[0].ttype == TokenType::String);
+        assert!(tokens[0].literal == Literal::StrLit(String::from(r"C:\new\tab")));
+    }
+
+    #[test]
+    fn triple_quoted_strings() {
+        let test = String::from("\"\"\"Ave,\n\"Caesar\"\n\"\"\";\nx");
+        let tokens = Scanner::scan(test);
+        let expected = Literal::StrLit(String::from("Ave,\n\"Caesar\"\n"));
+        assert!(tokens[0].literal == expected);
+        assert!(tokens[1] == get_semicolon(3));
+        assert!(tokens[2].line == 4);
+    }
+
+    #[test]
+    fn literal_braces() {
+        let tokens = Scanner::scan(String::from(r#""\{ x: 1 \}" r"{ x: 1 }";"#));
+        let expected = Literal::StrLit(String::from("{ x: 1 }"));
+        assert!(tokens[0].ttype == TokenType::String);
+        assert!(tokens[0].literal == expected);
+        assert!(tokens[1].ttype == TokenType::String);
+        assert!(tokens[1].literal == expected);
+        // An unescaped brace is an interpolation, so it has to be closed.
+        let mut s = Scanner::new(String::from("\"{ x: 1\";"));
+        s.next();
+        assert!(s.lex_string(false).is_err());
+    }
+
+    #[test]
+    fn interpolation() {
+        let test = String::from("\"Ave, {nomen}!\" \"{a +\n b}\\{}\";");
+        let tokens = Scanner::scan(test);
+        let expected = vec![
+            Token::new(
+                TokenType::Interpolation,
+                "\"Ave, {".to_string(),
+                Literal::StrLit("Ave, ".to_string()),
+                1,
+            ),
+            Token::new(
+                TokenType::Identifier,
+                "nomen".to_string(),
+                Literal::Empty,
+                1,
+            ),
+            Token::new(
+                TokenType::String,
+                "\"Ave, {nomen}!\"".to_string(),
+                Literal::StrLit("!".to_string()),
+                1,
+            ),
+            Token::new(
+                TokenType::Interpolation,
+                "\"{".to_string(),
+                Literal::StrLit("".to_string()),
+                1,
+            ),
+            Token::new(TokenType::Identifier, "a".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::Plus, "+".to_string(), Literal::Empty, 1),
+            Token::new(TokenType::Identifier, "b".to_string(), Literal::Empty, 2),
+            Token::new(
+                TokenType::String,
+                "\"{a +\n b}\\{}\"".to_string(),
+                Literal::StrLit("{}".to_string()),
+                2,
+            ),
+            get_semicolon(2),
+            get_eof(2),
+        ];
+        assert!(tokens == expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_interpolation() {
+        Scanner::scan(String::from("\"{}\";"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unterminated_interpolation() {
+        Scanner::scan(String::from("\"{a\";"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_escape() {
+        let test = String::from(r#""\q";"#);
+        Scanner::scan(test);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_unicode_escape() {
+        let test = String::from(r#""\u{D800}";"#);
+        Scanner::scan(test);
+    }
+
     #[test]
     #[should_panic]
     fn unterminated_block_comment() {
@@ -9,10 +9,16 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
+    Backslash,
     Minus,
+    Percent,
     Plus,
+    Question,
     Semicolon,
     Slash,
     Star,
@@ -26,15 +32,28 @@ pub enum TokenType {
     Greater,
     Less,
     LessEqual,
+    DotDotDot,
+    StarStar,
+    MinusEqual,
+    PercentEqual,
+    PlusEqual,
+    SlashEqual,
+    StarEqual,
 
     // Literals.
     Identifier,
     String,
+    /// A piece of string literal that is followed by an interpolated expression.
+    Interpolation,
     Number,
 
     // Keywords / logic ops etc
     And,
+    Break,
+    Catch,
     Class,
+    Const,
+    Continue,
     Else,
     False,
     Fn,
@@ -45,7 +64,9 @@ pub enum TokenType {
     Return,
     Super,
     Self_,
+    Throw,
     True,
+    Try,
     Var,
     While,
     Call,
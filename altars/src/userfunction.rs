@@ -1,62 +1,197 @@
 //! Type of all functions defined in Daemonica.
 use core::fmt;
-use std::fmt::Display;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
 use crate::{
-    ast::{Expr, Stmt, Value},
+    ast::{Param, Stmt, Value},
+    callable::{Arity, Callable},
+    class::Instance,
     environment::Environment,
-    interpreter::Interpreter,
+    interpreter::{Interpreter, RuntimeError, Unwind},
     token::Token,
 };
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(Clone)]
 pub struct UserFunction {
     symbol: Token,
     body: Vec<Stmt>,
-    paramlist: Vec<Token>,
+    paramlist: Vec<Param>,
+    /// The environment that the function was declared in. Calls run in a new
+    /// scope that is enclosed by this one, rather than by the caller's scope.
+    closure: Rc<RefCell<Environment>>,
 }
 
 impl UserFunction {
-    pub fn new(symbol: Token, body: Vec<Stmt>, paramlist: Vec<Token>) -> UserFunction {
+    pub fn new(
+        symbol: Token,
+        body: Vec<Stmt>,
+        paramlist: Vec<Param>,
+        closure: Rc<RefCell<Environment>>,
+    ) -> UserFunction {
         UserFunction {
             symbol,
             body,
             paramlist,
+            closure,
         }
     }
 
-    pub fn call(
+    /// Produce a copy of this method with `anima` bound to the given instance.
+    /// We do this by wrapping the closure in a new scope that defines `anima`.
+    pub fn bind(&self, instance: Rc<RefCell<Instance>>) -> UserFunction {
+        let mut scope = Environment::with_parent(self.closure.clone());
+        scope.define(String::from("anima"), Some(Value::Instance(instance)));
+        let mut bound = self.clone();
+        bound.closure = Rc::new(RefCell::new(scope));
+        bound
+    }
+
+    /// Bind the arguments of a call to the parameters in the new frame.
+    ///
+    /// Positional arguments fill the parameters in order, then named arguments
+    /// fill the ones they name. Anything left unfilled takes it's default,
+    /// which is evaluated in the frame so that it can refer to the parameters
+    /// before it. Extra positional arguments go to the rest parameter.
+    fn parse_arguments(
         &self,
         interpreter: &mut Interpreter,
-        args: Vec<Value>,
-    ) -> Result<(Environment, Option<Value>), (Environment, String)> {
-        //dbg!(args.clone());
-        let scope_copy = interpreter.environment.clone();
-        let scope_copy = self.parse_arguments(scope_copy.clone(), args.clone());
-        match interpreter.interpret_block(self.body.clone(), scope_copy.clone()) {
-            Ok(_) => {
-                // Execution successful, return the modified scope.
-                return Ok((interpreter.environment.clone(), interpreter.retval.clone()));
-            }
-            Err(x) => {
-                // Execution went wrong, return error message, and the old scope.
-                return Err((scope_copy, x));
+        frame: &Rc<RefCell<Environment>>,
+        mut args: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<(), RuntimeError> {
+        let (params, rest) = match self.paramlist.last() {
+            Some(last) if last.rest => (&self.paramlist[..self.paramlist.len() - 1], Some(last)),
+            _ => (&self.paramlist[..], None),
+        };
+        let extra = if args.len() > params.len() {
+            args.split_off(params.len())
+        } else {
+            Vec::new()
+        };
+        let mut slots: Vec<Option<Value>> = args.into_iter().map(Some).collect();
+        slots.resize(params.len(), None);
+        for (name, val) in named {
+            let pos = params.iter().position(|p| p.name.lexeme == name.lexeme);
+            match pos {
+                Some(i) if slots[i].is_none() => slots[i] = Some(val),
+                Some(_) => {
+                    let emsg = format!(
+                        "{} was passed the argument {} more than once. Line {}",
+                        self.symbol.lexeme, name.lexeme, name.line
+                    );
+                    return Err(RuntimeError::new("argument", emsg, Some(name.line)));
+                }
+                None => {
+                    let emsg = format!(
+                        "{} has no parameter named {}. Line {}",
+                        self.symbol.lexeme, name.lexeme, name.line
+                    );
+                    return Err(RuntimeError::new("argument", emsg, Some(name.line)));
+                }
             }
         }
+        for (param, slot) in params.iter().zip(slots) {
+            let val = match (slot, &param.default) {
+                (Some(val), _) => val,
+                (None, Some(default)) => {
+                    interpreter.interpret_expr_in(default.clone(), frame.clone())?
+                }
+                (None, None) => {
+                    let emsg = format!(
+                        "{} was called without a value for {}. Line {}",
+                        self.symbol.lexeme, param.name.lexeme, param.name.line
+                    );
+                    return Err(RuntimeError::new("argument", emsg, Some(param.name.line)));
+                }
+            };
+            frame
+                .borrow_mut()
+                .define(param.name.lexeme.clone(), Some(val));
+        }
+        if let Some(rest) = rest {
+            let extra = Value::List(Rc::new(RefCell::new(extra)));
+            frame
+                .borrow_mut()
+                .define(rest.name.lexeme.clone(), Some(extra));
+        }
+        Ok(())
+    }
+}
+
+impl Callable for UserFunction {
+    fn name(&self) -> &str {
+        &self.symbol.lexeme
+    }
+
+    fn arity(&self, _interpreter: &Interpreter) -> Arity {
+        let required = self
+            .paramlist
+            .iter()
+            .filter(|p| p.default.is_none() && !p.rest)
+            .count();
+        match self.paramlist.last() {
+            Some(last) if last.rest => Arity {
+                min: required,
+                max: None,
+            },
+            _ => Arity {
+                min: required,
+                max: Some(self.paramlist.len()),
+            },
+        }
     }
 
-    fn parse_arguments(&self, mut scope: Environment, args: Vec<Value>) -> Environment {
-        for (arg, name) in args.iter().zip(self.paramlist.iter()) {
-            scope.define(name.lexeme.clone(), Some(arg.clone()));
+    /// Each call gets a fresh scope (it's frame) enclosed by the function's
+    /// closure. The frame is thrown away once the body has finished running, so
+    /// parameters and locals never escape into the caller.
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.call_named(interpreter, args, Vec::new())
+    }
+
+    fn call_named(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        let frame = Rc::new(RefCell::new(Environment::with_parent(self.closure.clone())));
+        self.parse_arguments(interpreter, &frame, args, named)?;
+        match interpreter.interpret_block(self.body.clone(), frame) {
+            // Fell off the end of the body without returning anything.
+            Ok(_) => Ok(Value::Empty),
+            Err(Unwind::Return(val)) => Ok(val),
+            Err(unwind) => Err(unwind.into_error()),
         }
-        scope
+    }
+}
+
+/// Two functions are the same if they have the same definition, and close over
+/// the very same environment.
+impl PartialEq for UserFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.body == other.body
+            && self.paramlist == other.paramlist
+            && Rc::ptr_eq(&self.closure, &other.closure)
+    }
+}
+
+// The closure usually contains the function itself, so we skip it here
+// otherwise we would recurse forever.
+impl fmt::Debug for UserFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UserFunction")
+            .field("symbol", &self.symbol)
+            .field("body", &self.body)
+            .field("paramlist", &self.paramlist)
+            .finish()
     }
 }
 
 impl Display for UserFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let name = self.symbol.lexeme.clone();
-        let params: Vec<String> = self.paramlist.iter().map(|x| x.lexeme.clone()).collect();
+        let params: Vec<String> = self.paramlist.iter().map(|x| x.to_string()).collect();
         let params = params.join(", ");
         write!(f, "{} :: ({})", name, params)
     }